@@ -1,15 +1,20 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::iproduct;
+use itertools::{iproduct, Itertools};
 use parse_display::{Display, FromStr};
+use std::error::Error;
+use std::fmt::{self, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
 
-#[derive(Display, FromStr, Debug, Copy, Clone)]
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
 #[display("{x},{y}")]
 pub struct Point {
     x: usize,
     y: usize,
 }
 
-#[derive(Display, FromStr, Debug, Copy, Clone)]
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Instruction {
     #[display("turn on {0} through {1}")]
     TurnOn(Point, Point),
@@ -21,69 +26,513 @@ pub enum Instruction {
     Toggle(Point, Point),
 }
 
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
+#[display("{width}x{height}")]
+pub struct Size {
+    width: usize,
+    height: usize,
+}
+
+const LIGHTS: Size = Size::new(1_000, 1_000);
+
+impl Size {
+    pub const fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    const fn contains(self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    fn validate(self, insts: &[Instruction]) -> Result<Vec<Instruction>, GridError> {
+        insts
+            .iter()
+            .map(|instr| {
+                let instr = instr.normalized();
+                let (from, to) = instr.corners();
+
+                if self.contains(from) && self.contains(to) {
+                    Ok(instr)
+                } else {
+                    Err(GridError::OutOfBounds { instr, size: self })
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GridError {
+    OutOfBounds { instr: Instruction, size: Size },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { instr, size } => {
+                write!(f, "\"{instr}\" reaches outside the {size} grid")
+            }
+        }
+    }
+}
+
+impl Error for GridError {}
+
+impl From<GridError> for io::Error {
+    fn from(err: GridError) -> Self {
+        Self::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+trait Lights<T> {
+    fn handle_instruction(&mut self, from: Point, to: Point, f: fn(&mut T));
+}
+
+const EXPORT_DIR_VAR: &str = "DAY06_EXPORT_DIR";
+const FRAME_STEP: usize = 20;
+
 struct Grid<T>
 where
     T: Default + Clone,
 {
     lights: Vec<T>,
-    size: usize,
+    size: Size,
 }
 
 impl<T> Grid<T>
 where
     T: Default + Clone,
 {
-    fn with_size(size: usize) -> Self {
+    fn with_size(size: Size) -> Self {
         Self {
-            lights: vec![T::default(); size * size],
+            lights: vec![T::default(); size.width * size.height],
             size,
         }
     }
 
+    // lights are stored column by column
+    const fn index(&self, x: usize, y: usize) -> usize {
+        x * self.size.height + y
+    }
+}
+
+impl<T> Lights<T> for Grid<T>
+where
+    T: Default + Clone,
+{
     fn handle_instruction(&mut self, from: Point, to: Point, f: fn(&mut T)) {
         for (x, y) in iproduct!(from.x..=to.x, from.y..=to.y) {
-            let index = x * self.size + y;
+            let index = self.index(x, y);
             let elem = &mut self.lights[index];
             f(elem);
         }
     }
 }
 
+trait Image {
+    const EXTENSION: &'static str;
+
+    fn to_image(&self) -> Vec<u8>;
+}
+
+// lit lights are drawn white, so they are the zero bits of the bitmap
+impl Image for Grid<bool> {
+    const EXTENSION: &'static str = "pbm";
+
+    fn to_image(&self) -> Vec<u8> {
+        let Size { width, height } = self.size;
+        let mut image = format!("P4\n{width} {height}\n").into_bytes();
+
+        for y in 0..height {
+            let row = (0..width).map(|x| !self.lights[self.index(x, y)]);
+            for bits in &row.chunks(8) {
+                let byte = bits
+                    .enumerate()
+                    .fold(0u8, |acc, (idx, bit)| acc | (u8::from(bit) << (7 - idx)));
+                image.push(byte);
+            }
+        }
+
+        image
+    }
+}
+
+impl Image for Grid<usize> {
+    const EXTENSION: &'static str = "pgm";
+
+    fn to_image(&self) -> Vec<u8> {
+        let max_value = self
+            .lights
+            .iter()
+            .max()
+            .map_or(1, |it| (*it).clamp(1, usize::from(u16::MAX)));
+        let Size { width, height } = self.size;
+        let mut image = format!("P5\n{width} {height}\n{max_value}\n").into_bytes();
+
+        for y in 0..height {
+            for x in 0..width {
+                let value = u16::try_from(self.lights[self.index(x, y)]).unwrap_or(u16::MAX);
+
+                // samples above 255 take two bytes, most significant first
+                if max_value > usize::from(u8::MAX) {
+                    image.extend(value.to_be_bytes());
+                } else {
+                    image.push(value.to_be_bytes()[1]);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+fn write_image<I: Image>(image: &I, path: &Path) -> io::Result<PathBuf> {
+    let path = path.with_extension(I::EXTENSION);
+    fs::write(&path, image.to_image())?;
+    Ok(path)
+}
+
+fn write_frames<M: LightModel>(
+    insts: &[Instruction],
+    size: Size,
+    every: usize,
+    dir: &Path,
+    name: &str,
+) -> io::Result<(Vec<PathBuf>, Grid<M::Light>)>
+where
+    Grid<M::Light>: Image,
+{
+    let insts = size.validate(insts)?;
+    let mut g = Grid::with_size(size);
+    let mut frames = Vec::new();
+
+    for (idx, chunk) in insts.chunks(every.max(1)).enumerate() {
+        replay::<M>(&mut g, chunk);
+        frames.push(write_image(&g, &dir.join(format!("{name}_{idx:04}")))?);
+    }
+
+    // the grid after the last frame is the final one
+    Ok((frames, g))
+}
+
+// every block between two neighbouring boundaries is lit the same way,
+// so only one light per block is tracked
+struct CompressedGrid<T>
+where
+    T: Default + Clone,
+{
+    xs: Vec<usize>,
+    ys: Vec<usize>,
+    lights: Vec<T>,
+}
+
+impl<T> CompressedGrid<T>
+where
+    T: Default + Clone,
+{
+    fn new(insts: &[Instruction]) -> Self {
+        let corners = insts.iter().map(Instruction::corners).collect_vec();
+        let boundaries = |coord: fn(Point) -> usize| {
+            corners
+                .iter()
+                .flat_map(|(from, to)| [coord(*from), coord(*to) + 1])
+                .sorted_unstable()
+                .dedup()
+                .collect_vec()
+        };
+
+        let xs = boundaries(|it| it.x);
+        let ys = boundaries(|it| it.y);
+        let blocks = xs.len().saturating_sub(1) * ys.len().saturating_sub(1);
+
+        Self {
+            xs,
+            ys,
+            lights: vec![T::default(); blocks],
+        }
+    }
+
+    fn block_range(boundaries: &[usize], from: usize, to: usize) -> Range<usize> {
+        let start = boundaries.partition_point(|it| *it < from);
+        let end = boundaries.partition_point(|it| *it <= to);
+        start..end
+    }
+
+    fn total(&self, size: Size, value: fn(&T) -> usize) -> usize {
+        let height = self.ys.len().saturating_sub(1);
+        let mut untouched = size.width * size.height;
+
+        let touched = self
+            .lights
+            .iter()
+            .enumerate()
+            .map(|(idx, light)| {
+                let (x, y) = (idx / height, idx % height);
+                let area = (self.xs[x + 1] - self.xs[x]) * (self.ys[y + 1] - self.ys[y]);
+                untouched -= area;
+                value(light) * area
+            })
+            .sum::<usize>();
+
+        // lights outside every instruction keep their default value
+        touched + value(&T::default()) * untouched
+    }
+}
+
+impl<T> Lights<T> for CompressedGrid<T>
+where
+    T: Default + Clone,
+{
+    fn handle_instruction(&mut self, from: Point, to: Point, f: fn(&mut T)) {
+        let height = self.ys.len().saturating_sub(1);
+        let xs = Self::block_range(&self.xs, from.x, to.x);
+        let ys = Self::block_range(&self.ys, from.y, to.y);
+
+        for (x, y) in iproduct!(xs, ys) {
+            f(&mut self.lights[x * height + y]);
+        }
+    }
+}
+
+impl Instruction {
+    const fn corners(&self) -> (Point, Point) {
+        match *self {
+            Self::TurnOn(from, to) | Self::TurnOff(from, to) | Self::Toggle(from, to) => (from, to),
+        }
+    }
+
+    const fn with_corners(self, from: Point, to: Point) -> Self {
+        match self {
+            Self::TurnOn(..) => Self::TurnOn(from, to),
+            Self::TurnOff(..) => Self::TurnOff(from, to),
+            Self::Toggle(..) => Self::Toggle(from, to),
+        }
+    }
+
+    // the corners may be given in any order
+    fn normalized(self) -> Self {
+        let (a, b) = self.corners();
+        let from = Point {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+        };
+        let to = Point {
+            x: a.x.max(b.x),
+            y: a.y.max(b.y),
+        };
+
+        self.with_corners(from, to)
+    }
+}
+
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
+enum Line {
+    #[display("grid: {0}")]
+    Grid(Size),
+
+    #[display("{0}")]
+    Instruction(Instruction),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    size: Size,
+    insts: Vec<Instruction>,
+}
+
+// an optional "grid: <width>x<height>" line overrides the size of the grid
 #[aoc_generator(day6)]
-pub fn generate(inp: &str) -> Vec<Instruction> {
-    inp.lines().filter_map(|it| it.parse().ok()).collect()
+pub fn generate(inp: &str) -> Program {
+    let mut program = Program {
+        size: LIGHTS,
+        insts: Vec::new(),
+    };
+
+    for line in inp.lines().filter_map(|it| it.trim().parse::<Line>().ok()) {
+        match line {
+            Line::Grid(size) => program.size = size,
+            Line::Instruction(instr) => program.insts.push(instr),
+        }
+    }
+
+    program
 }
 
-#[aoc(day6, part1)]
-pub fn part1(insts: &[Instruction]) -> usize {
-    let mut g = Grid::with_size(1_000);
+pub trait LightModel {
+    type Light: Default + Clone;
+
+    fn turn_on(light: &mut Self::Light);
+    fn turn_off(light: &mut Self::Light);
+    fn toggle(light: &mut Self::Light);
+
+    // how much a single light adds to the grid total, lights no instruction
+    // reaches count with the value of `Self::Light::default()`
+    fn value(light: &Self::Light) -> usize;
+}
+
+pub struct OnOff;
+
+impl LightModel for OnOff {
+    type Light = bool;
+
+    fn turn_on(light: &mut bool) {
+        *light = true;
+    }
+
+    fn turn_off(light: &mut bool) {
+        *light = false;
+    }
+
+    fn toggle(light: &mut bool) {
+        *light = !*light;
+    }
+
+    fn value(light: &bool) -> usize {
+        usize::from(*light)
+    }
+}
+
+pub struct Brightness;
+
+impl LightModel for Brightness {
+    type Light = usize;
+
+    fn turn_on(light: &mut usize) {
+        *light += 1;
+    }
+
+    fn turn_off(light: &mut usize) {
+        *light = light.saturating_sub(1);
+    }
+
+    fn toggle(light: &mut usize) {
+        *light += 2;
+    }
+
+    fn value(light: &usize) -> usize {
+        *light
+    }
+}
+
+pub struct ToggleCount;
+
+impl LightModel for ToggleCount {
+    type Light = usize;
+
+    fn turn_on(_: &mut usize) {}
+
+    fn turn_off(_: &mut usize) {}
+
+    fn toggle(light: &mut usize) {
+        *light += 1;
+    }
+
+    fn value(light: &usize) -> usize {
+        *light
+    }
+}
+
+// turning on adds red, toggling shifts every channel over by one colour
+pub struct Rgb;
+
+impl LightModel for Rgb {
+    type Light = [u8; 3];
+
+    fn turn_on(light: &mut [u8; 3]) {
+        light[0] = light[0].saturating_add(1);
+    }
+
+    fn turn_off(light: &mut [u8; 3]) {
+        *light = [0; 3];
+    }
+
+    fn toggle(light: &mut [u8; 3]) {
+        light.rotate_right(1);
+    }
+
+    fn value(light: &[u8; 3]) -> usize {
+        light.iter().map(|it| usize::from(*it)).sum()
+    }
+}
 
+fn replay<M: LightModel>(g: &mut impl Lights<M::Light>, insts: &[Instruction]) {
     for instr in insts {
-        match *instr {
-            Instruction::TurnOn(from, to) => g.handle_instruction(from, to, |it| *it = true),
-            Instruction::TurnOff(from, to) => g.handle_instruction(from, to, |it| *it = false),
-            Instruction::Toggle(from, to) => g.handle_instruction(from, to, |it| *it = !*it),
+        let (from, to) = instr.corners();
+        let f: fn(&mut M::Light) = match instr {
+            Instruction::TurnOn(..) => M::turn_on,
+            Instruction::TurnOff(..) => M::turn_off,
+            Instruction::Toggle(..) => M::toggle,
         };
+
+        g.handle_instruction(from, to, f);
     }
+}
+
+fn total<M: LightModel>(insts: &[Instruction], size: Size) -> Result<usize, GridError> {
+    let insts = size.validate(insts)?;
+    let mut g = CompressedGrid::new(&insts);
+    replay::<M>(&mut g, &insts);
+    Ok(g.total(size, M::value))
+}
 
-    g.lights.iter().filter(|it| **it).count()
+fn total_cells<M: LightModel>(insts: &[Instruction], size: Size) -> Result<usize, GridError> {
+    let mut g = Grid::with_size(size);
+    replay::<M>(&mut g, &size.validate(insts)?);
+    Ok(g.lights.iter().map(M::value).sum())
+}
+
+#[aoc(day6, part1)]
+pub fn part1(program: &Program) -> Result<usize, GridError> {
+    total::<OnOff>(&program.insts, program.size)
+}
+
+#[aoc(day6, part1, Cells)]
+pub fn part1_cells(program: &Program) -> Result<usize, GridError> {
+    total_cells::<OnOff>(&program.insts, program.size)
+}
+
+#[aoc(day6, part1, Toggles)]
+pub fn part1_toggles(program: &Program) -> Result<usize, GridError> {
+    total::<ToggleCount>(&program.insts, program.size)
+}
+
+// writes the final grid plus its frames, returning how many images were written
+fn export<M: LightModel>(program: &Program, dir: &Path, name: &str) -> io::Result<usize>
+where
+    Grid<M::Light>: Image,
+{
+    let (frames, g) = write_frames::<M>(&program.insts, program.size, FRAME_STEP, dir, name)?;
+    write_image(&g, &dir.join(name))?;
+
+    Ok(frames.len() + 1)
+}
+
+// only writes images when asked to, they take up tens of megabytes
+#[aoc(day6, part1, Export)]
+pub fn part1_export(program: &Program) -> io::Result<String> {
+    let Some(dir) = env::var_os(EXPORT_DIR_VAR).map(PathBuf::from) else {
+        return Ok(format!("set {EXPORT_DIR_VAR} to export images"));
+    };
+    fs::create_dir_all(&dir)?;
+
+    let images =
+        export::<OnOff>(program, &dir, "part1")? + export::<Brightness>(program, &dir, "part2")?;
+    Ok(format!("{images} images in {}", dir.display()))
 }
 
 #[aoc(day6, part2)]
-pub fn part2(insts: &[Instruction]) -> usize {
-    let mut g = Grid::<usize>::with_size(1_000);
+pub fn part2(program: &Program) -> Result<usize, GridError> {
+    total::<Brightness>(&program.insts, program.size)
+}
 
-    for instr in insts {
-        match *instr {
-            Instruction::TurnOn(from, to) => g.handle_instruction(from, to, |it| *it += 1),
-            Instruction::TurnOff(from, to) => {
-                g.handle_instruction(from, to, |it| *it = it.saturating_sub(1));
-            }
-            Instruction::Toggle(from, to) => g.handle_instruction(from, to, |it| *it += 2),
-        }
-    }
+#[aoc(day6, part2, Cells)]
+pub fn part2_cells(program: &Program) -> Result<usize, GridError> {
+    total_cells::<Brightness>(&program.insts, program.size)
+}
 
-    g.lights.iter().sum()
+#[aoc(day6, part2, Rgb)]
+pub fn part2_rgb(program: &Program) -> Result<usize, GridError> {
+    total::<Rgb>(&program.insts, program.size)
 }
 
 #[cfg(test)]
@@ -100,7 +549,7 @@ mod tests {
         for (inp, expected) in test_data {
             let data = generate(inp);
             let res = part1(&data);
-            assert_eq!(res, expected);
+            assert_eq!(res, Ok(expected));
         }
     }
 
@@ -114,7 +563,223 @@ mod tests {
         for (inp, expected) in test_data {
             let data = generate(inp);
             let res = part2(&data);
-            assert_eq!(res, expected);
+            assert_eq!(res, Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_compressed_matches_cells() {
+        let inp = "turn on 0,0 through 999,999\n\
+                   toggle 0,0 through 999,0\n\
+                   turn off 499,499 through 500,500\n\
+                   toggle 10,20 through 600,700\n\
+                   turn off 300,100 through 900,200\n\
+                   turn on 650,0 through 650,999\n\
+                   turn off 0,0 through 5,5\n\
+                   turn off 0,0 through 5,5";
+
+        let data = generate(inp);
+        assert_eq!(part1(&data), part1_cells(&data));
+        assert_eq!(part2(&data), part2_cells(&data));
+    }
+
+    #[test]
+    fn test_huge_grid() {
+        let inp = "turn on 0,0 through 999999,999999\n\
+                   toggle 0,0 through 999999,0\n\
+                   turn off 1,1 through 499999,999999";
+
+        let data = generate(inp);
+        let size = Size {
+            width: 1_000_000,
+            height: 1_000_000,
+        };
+        let turned_off = 499_999 * 999_999;
+        assert_eq!(
+            total::<OnOff>(&data.insts, size),
+            Ok(1_000_000 * 1_000_000 - 1_000_000 - turned_off)
+        );
+        assert_eq!(
+            total::<Brightness>(&data.insts, size),
+            Ok(1_000_000 * 1_000_000 + 2 * 1_000_000 - turned_off)
+        );
+    }
+
+    #[test]
+    fn test_pbm_image() {
+        let mut g = Grid::with_size(Size {
+            width: 10,
+            height: 10,
+        });
+        replay::<OnOff>(
+            &mut g,
+            &generate("turn on 0,0 through 8,1\ntoggle 1,1 through 1,1").insts,
+        );
+
+        let mut expected = b"P4\n10 10\n".to_vec();
+        expected.extend([0b0000_0000, 0b0100_0000, 0b0100_0000, 0b0100_0000]);
+        expected.extend([0b1111_1111, 0b1100_0000].repeat(8));
+
+        assert_eq!(g.to_image(), expected);
+    }
+
+    #[test]
+    fn test_pgm_image() {
+        let mut g = Grid::<usize>::with_size(Size {
+            width: 2,
+            height: 2,
+        });
+        replay::<Brightness>(
+            &mut g,
+            &generate("turn on 0,0 through 1,0\ntoggle 1,0 through 1,1").insts,
+        );
+        assert_eq!(g.to_image(), b"P5\n2 2\n3\n\x01\x03\x00\x02".to_vec());
+
+        g.lights[0] = 300;
+        let mut expected = b"P5\n2 2\n300\n".to_vec();
+        expected.extend([1, 44, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(g.to_image(), expected);
+    }
+
+    #[test]
+    fn test_write_frames() {
+        let dir = std::env::temp_dir().join(format!("day06_frames_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Temporary directory is writable");
+
+        let data = generate(
+            "turn on 0,0 through 999,999\n\
+             toggle 0,0 through 999,0\n\
+             turn off 0,0 through 9,9",
+        );
+        let (frames, g) = write_frames::<OnOff>(&data.insts, LIGHTS, 2, &dir, "lights")
+            .expect("Frames can be written");
+        assert_eq!(frames.iter().filter_map(|it| it.file_name()).count(), 2);
+
+        let last = fs::read(dir.join("lights_0001.pbm")).unwrap_or_default();
+        assert!(last.starts_with(b"P4\n1000 1000\n"));
+        assert_eq!(last.len(), b"P4\n1000 1000\n".len() + 1000 * 125);
+        assert_eq!(g.to_image(), last);
+
+        fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn test_rectangular_grid() {
+        let size = Size {
+            width: 3,
+            height: 5,
+        };
+        let data = generate("turn on 0,0 through 2,4\ntoggle 2,4 through 0,3");
+
+        assert_eq!(total::<OnOff>(&data.insts, size), Ok(9));
+        assert_eq!(total::<Brightness>(&data.insts, size), Ok(15 + 12));
+
+        let mut g = Grid::with_size(size);
+        replay::<OnOff>(&mut g, &size.validate(&data.insts).unwrap_or_default());
+        let mut expected = b"P4\n3 5\n".to_vec();
+        expected.extend([0, 0, 0, 0b1110_0000, 0b1110_0000]);
+        assert_eq!(g.to_image(), expected);
+    }
+
+    #[test]
+    fn test_grid_line() {
+        let data = generate("grid: 3x5\nturn on 0,0 through 2,4\ntoggle 2,4 through 0,3");
+        assert_eq!(data.size, Size::new(3, 5));
+        assert_eq!(data.insts.len(), 2);
+
+        assert_eq!(part1(&data), Ok(9));
+        assert_eq!(part1_cells(&data), Ok(9));
+        assert_eq!(part2(&data), Ok(15 + 12));
+
+        let data = generate("grid: 3x5\nturn on 0,0 through 4,2");
+        assert_eq!(
+            part1(&data).map_err(|it| it.to_string()),
+            Err(String::from(
+                "\"turn on 0,0 through 4,2\" reaches outside the 3x5 grid"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_reversed_corners() {
+        let data = generate("turn on 999,999 through 0,0\ntoggle 999,0 through 0,0");
+        assert_eq!(part1(&data), Ok(1000 * 1000 - 1000));
+        assert_eq!(part1_cells(&data), Ok(1000 * 1000 - 1000));
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let data = generate("turn on 0,0 through 999,999\ntoggle 0,1000 through 5,5");
+
+        let err = GridError::OutOfBounds {
+            instr: data.insts[1].normalized(),
+            size: LIGHTS,
+        };
+        assert_eq!(part1(&data), Err(err));
+        assert_eq!(part2_cells(&data), Err(err));
+        assert_eq!(
+            err.to_string(),
+            "\"toggle 0,5 through 5,1000\" reaches outside the 1000x1000 grid"
+        );
+    }
+
+    #[test]
+    fn test_custom_models() {
+        let data = generate(
+            "turn on 0,0 through 1,1\n\
+             toggle 0,0 through 0,1\n\
+             turn on 1,0 through 1,1\n\
+             toggle 0,0 through 1,0\n\
+             turn off 1,1 through 1,1",
+        );
+        let size = Size {
+            width: 2,
+            height: 2,
+        };
+
+        assert_eq!(total::<ToggleCount>(&data.insts, size), Ok(4));
+        assert_eq!(total_cells::<ToggleCount>(&data.insts, size), Ok(4));
+
+        let mut g = Grid::with_size(size);
+        replay::<Rgb>(&mut g, &data.insts);
+        assert_eq!(g.lights, vec![[0, 0, 1], [0, 1, 0], [0, 2, 0], [0; 3]]);
+        assert_eq!(total::<Rgb>(&data.insts, size), Ok(4));
+    }
+
+    struct DarkCount;
+
+    impl LightModel for DarkCount {
+        type Light = bool;
+
+        fn turn_on(light: &mut bool) {
+            OnOff::turn_on(light);
+        }
+
+        fn turn_off(light: &mut bool) {
+            OnOff::turn_off(light);
         }
+
+        fn toggle(light: &mut bool) {
+            OnOff::toggle(light);
+        }
+
+        fn value(light: &bool) -> usize {
+            usize::from(!*light)
+        }
+    }
+
+    #[test]
+    fn test_untouched_lights() {
+        let data = generate("turn on 10,10 through 19,19\ntoggle 15,0 through 15,999");
+
+        assert_eq!(
+            total::<DarkCount>(&data.insts, LIGHTS),
+            Ok(1000 * 1000 - 100 - 990 + 10)
+        );
+        assert_eq!(
+            total::<DarkCount>(&data.insts, LIGHTS),
+            total_cells::<DarkCount>(&data.insts, LIGHTS)
+        );
+        assert_eq!(total::<DarkCount>(&[], LIGHTS), Ok(1000 * 1000));
     }
 }
@@ -1,90 +1,73 @@
+use crate::graph::{BestRoutes, Graph, Kind, MissingEdges};
+use crate::held_karp::Goal;
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
 use parse_display::{Display, FromStr};
-use std::collections::HashSet;
 
 #[derive(Display, FromStr, Debug, Clone)]
 #[display("{lhs} to {rhs} = {dist}")]
-pub struct Connection {
+struct Connection {
     lhs: String,
     rhs: String,
-    dist: usize,
-}
-
-impl Connection {
-    fn connects(&self, city_a: &str, city_b: &str) -> bool {
-        (self.lhs.eq(city_a) && self.rhs.eq(city_b)) || (self.lhs.eq(city_b) && self.rhs.eq(city_a))
-    }
+    dist: i64,
 }
 
 #[aoc_generator(day9)]
-pub fn generate(inp: &str) -> Vec<Connection> {
-    inp.lines().filter_map(|it| it.parse().ok()).collect()
-}
-
-fn get_nodes(conns: &[Connection]) -> HashSet<&String> {
-    let mut nodes = HashSet::new();
-
-    for it in conns {
-        nodes.insert(&it.lhs);
-        nodes.insert(&it.rhs);
-    }
-
-    nodes
-}
-
-fn find_connection<'a>(from: &str, to: &str, conns: &'a [Connection]) -> Option<&'a Connection> {
-    conns.iter().find(|it| it.connects(from, to))
+pub fn generate(inp: &str) -> Result<Graph, MissingEdges> {
+    let graph = inp
+        .lines()
+        .filter_map(|it| it.parse::<Connection>().ok())
+        .fold(Graph::new(Kind::Symmetric), |mut acc, it| {
+            acc.add_edge(&it.lhs, &it.rhs, it.dist);
+            acc
+        });
+
+    graph.ensure_complete()?;
+    Ok(graph)
 }
 
-fn route_length(route: &[&String], conns: &[Connection]) -> usize {
-    let mut result = 0;
-
-    for window in route.windows(2) {
-        if let [from, to] = &window {
-            if let Some(conn) = find_connection(from, to, conns) {
-                result += conn.dist;
-            }
-        }
-    }
-
-    result
-}
-
-fn get_distances(conns: &[Connection]) -> Vec<usize> {
-    let nodes = get_nodes(conns);
-    let num_nodes = nodes.len();
-
-    nodes
-        .into_iter()
-        .permutations(num_nodes)
-        .map(|it| route_length(&it, conns))
-        .collect()
+fn best_route(graph: &Graph, goal: Goal) -> Option<BestRoutes> {
+    graph.best_path(goal, |a, b| graph.route_length(&[a, b]))
 }
 
 #[aoc(day9, part1)]
-pub fn part1(conns: &[Connection]) -> Option<usize> {
-    get_distances(conns).into_iter().min()
+pub fn part1(graph: &Graph) -> Option<BestRoutes> {
+    best_route(graph, Goal::Shortest)
 }
 
 #[aoc(day9, part2)]
-pub fn part2(conns: &[Connection]) -> Option<usize> {
-    get_distances(conns).into_iter().max()
+pub fn part2(graph: &Graph) -> Option<BestRoutes> {
+    best_route(graph, Goal::Longest)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn route_length(graph: &Graph, names: &[String]) -> Option<i64> {
+        let route = names
+            .iter()
+            .filter_map(|name| graph.nodes().find(|it| graph.name(*it) == name))
+            .collect::<Vec<_>>();
+
+        graph.route_length(&route)
+    }
+
     #[test]
     fn test_sample_p1() {
         let inp = "London to Dublin = 464\n\
             London to Belfast = 518\n\
             Dublin to Belfast = 141";
 
-        let gen = generate(inp);
+        let gen = generate(inp).expect("Sample graph is complete");
         let res = part1(&gen);
-        assert_eq!(res, Some(605));
+        assert_eq!(res.as_ref().map(|it| it.length), Some(605));
+        for route in res.iter().flat_map(|it| &it.routes) {
+            assert_eq!(route_length(&gen, route), Some(605));
+        }
+        assert_eq!(
+            res.map(|it| it.to_string()),
+            Some(String::from("London -> Dublin -> Belfast = 605"))
+        );
     }
 
     #[test]
@@ -93,9 +76,28 @@ mod tests {
             London to Belfast = 518\n\
             Dublin to Belfast = 141";
 
-        let gen = generate(inp);
+        let gen = generate(inp).expect("Sample graph is complete");
         let res = part2(&gen);
         assert!(res.is_some());
-        assert_eq!(res, Some(982));
+        assert_eq!(res.as_ref().map(|it| it.length), Some(982));
+        for route in res.iter().flat_map(|it| &it.routes) {
+            assert_eq!(route_length(&gen, route), Some(982));
+        }
+        assert_eq!(
+            res.map(|it| it.to_string()),
+            Some(String::from("Dublin -> London -> Belfast = 982"))
+        );
+    }
+
+    #[test]
+    fn test_missing_connection() {
+        let inp = "London to Dublin = 464\n\
+            London to Belfast = 518";
+
+        let res = generate(inp).map_err(|it| it.to_string());
+        assert_eq!(
+            res.err(),
+            Some(String::from("no connection between: Dublin -> Belfast"))
+        );
     }
 }
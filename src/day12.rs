@@ -7,7 +7,7 @@ pub fn generate(inp: &str) -> Option<Value> {
 }
 
 fn is_red(val: &Value) -> bool {
-    val.as_str().map_or(false, |s| s.eq("red"))
+    val.as_str().is_some_and(|s| s.eq("red"))
 }
 
 fn is_red_object(obj: &Map<String, Value>) -> bool {
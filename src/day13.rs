@@ -1,7 +1,11 @@
+use crate::graph::{BestRoutes, Graph, Kind, NodeId};
+use crate::held_karp::Goal;
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
 use parse_display::{Display, FromStr};
-use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Formatter};
+use std::path::Path;
+use std::{fs, io};
 
 #[derive(Display, FromStr, Debug, Clone)]
 enum Connection {
@@ -12,109 +16,153 @@ enum Connection {
     Decrease(String, i64, String),
 }
 
-pub struct PeopleData {
-    people: Vec<String>,
-    happiness: HashMap<String, HashMap<String, i64>>,
-}
+const GUESTS_FILE: &str = "input/2015/day13_guests.txt";
 
-#[aoc_generator(day13)]
-pub fn generate(inp: &str) -> PeopleData {
-    let happiness: HashMap<String, HashMap<String, i64>> = inp
-        .lines()
-        .filter_map(|it| it.parse::<Connection>().ok())
-        .fold(HashMap::new(), |mut acc, it| {
-            let (pa, val, pb) = match it {
-                Connection::Increase(pa, val, pb) => (pa, val, pb),
-                Connection::Decrease(pa, val, pb) => (pa, -val, pb),
-            };
-
-            acc.entry(pa)
-                .and_modify(|e| {
-                    e.insert(pb.clone(), val);
-                })
-                .or_insert_with(|| HashMap::from([(pb, val)]));
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Table {
+    Circular,
+    Linear,
+}
 
-            acc
-        });
+#[derive(Debug)]
+pub enum GuestError {
+    Io(io::Error),
+    NotARule { line: usize, text: String },
+    Unrecognized { line: usize, text: String },
+    NoGuests,
+}
 
-    PeopleData {
-        people: happiness.keys().cloned().collect_vec(),
-        happiness,
+impl fmt::Display for GuestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "can't read guests: {err}"),
+            Self::NotARule { line, text } => {
+                write!(f, "line {line}: \"{text}\" is not a happiness rule")
+            }
+            Self::Unrecognized { line, text } => write!(
+                f,
+                "line {line}: \"{text}\" is neither a happiness rule nor a guest name"
+            ),
+            Self::NoGuests => write!(f, "there is nobody to seat"),
+        }
     }
 }
 
-fn happiness_for_seating_pair(
-    person_a: &str,
-    person_b: &str,
-    data: &HashMap<String, HashMap<String, i64>>,
-) -> i64 {
-    let a_happiness = data
-        .get(person_a)
-        .and_then(|it| it.get(person_b).copied())
-        .unwrap_or_default();
-
-    let b_happiness = data
-        .get(person_b)
-        .and_then(|it| it.get(person_a).copied())
-        .unwrap_or_default();
+impl Error for GuestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
-    a_happiness + b_happiness
+impl From<io::Error> for GuestError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
 }
 
-fn calc_happiness(order: &[&String], data: &HashMap<String, HashMap<String, i64>>) -> i64 {
-    let mut result = 0;
+// a guest who is indifferent to everyone
+pub fn add_guest(graph: &Graph, name: &str) -> Graph {
+    let mut graph = graph.clone();
+    graph.add_node(name);
+    graph
+}
 
-    for window in order.windows(2) {
-        if let [person_a, person_b] = &window {
-            result += happiness_for_seating_pair(person_a, person_b, data);
+// only the extra guests file may list bare names, for guests nobody has rules about
+fn read_lines(graph: &mut Graph, text: &str, bare_names: bool) -> Result<(), GuestError> {
+    for (idx, line) in text.lines().map(str::trim).enumerate() {
+        match line.parse::<Connection>() {
+            Ok(Connection::Increase(pa, val, pb)) => graph.add_edge(&pa, &pb, val),
+            Ok(Connection::Decrease(pa, val, pb)) => graph.add_edge(&pa, &pb, -val),
+            Err(_) if line.is_empty() => {}
+            Err(_) if bare_names && !line.contains(char::is_whitespace) => {
+                graph.add_node(line);
+            }
+            Err(_) if bare_names => {
+                return Err(GuestError::Unrecognized {
+                    line: idx + 1,
+                    text: line.to_string(),
+                })
+            }
+            Err(_) => {
+                return Err(GuestError::NotARule {
+                    line: idx + 1,
+                    text: line.to_string(),
+                })
+            }
         }
     }
 
-    // match first and last too
-    if let Some(person_a) = order.first() {
-        if let Some(person_b) = order.last() {
-            result += happiness_for_seating_pair(person_a, person_b, data);
-        }
-    }
+    Ok(())
+}
 
-    result
+pub fn add_guests(graph: &Graph, guests: &str) -> Result<Graph, GuestError> {
+    let mut graph = graph.clone();
+    read_lines(&mut graph, guests, true)?;
+    Ok(graph)
 }
 
-fn find_best_seating_arrangement(
-    data: &HashMap<String, HashMap<String, i64>>,
-    people: &[String],
-) -> i64 {
-    let num_people = people.len();
+pub fn load_guests(graph: &Graph, path: impl AsRef<Path>) -> Result<Graph, GuestError> {
+    let guests = fs::read_to_string(path)?;
+    add_guests(graph, &guests)
+}
+
+#[aoc_generator(day13)]
+pub fn generate(inp: &str) -> Result<Graph, GuestError> {
+    let mut graph = Graph::new(Kind::Asymmetric);
+    read_lines(&mut graph, inp, false)?;
+    Ok(graph)
+}
+
+fn happiness_for_seating_pair(person_a: NodeId, person_b: NodeId, graph: &Graph) -> i64 {
+    let a_happiness = graph.weight(person_a, person_b).unwrap_or_default();
+    let b_happiness = graph.weight(person_b, person_a).unwrap_or_default();
 
-    people
-        .iter()
-        .permutations(num_people)
-        .map(|it| calc_happiness(&it, data))
-        .max()
-        .unwrap_or_default()
+    a_happiness + b_happiness
+}
+
+fn find_best_seating_arrangement(graph: &Graph, table: Table) -> Option<BestRoutes> {
+    let happiness = |a, b| Some(happiness_for_seating_pair(a, b, graph));
+
+    match table {
+        Table::Circular => graph.best_cycle(Goal::Longest, happiness),
+        Table::Linear => graph.best_path(Goal::Longest, happiness),
+    }
 }
 
 #[aoc(day13, part1)]
-pub fn part1(data: &PeopleData) -> i64 {
-    let people = &data.people;
-    find_best_seating_arrangement(&data.happiness, people)
+pub fn part1(graph: &Graph) -> Option<BestRoutes> {
+    find_best_seating_arrangement(graph, Table::Circular)
+}
+
+#[aoc(day13, part1, Linear)]
+pub fn part1_linear(graph: &Graph) -> Option<BestRoutes> {
+    find_best_seating_arrangement(graph, Table::Linear)
 }
 
 #[aoc(day13, part2)]
-pub fn part2(data: &PeopleData) -> i64 {
-    let mut people = data.people.clone();
-    people.push(String::from("Self"));
+pub fn part2(graph: &Graph) -> Option<BestRoutes> {
+    find_best_seating_arrangement(&add_guest(graph, "Self"), Table::Circular)
+}
+
+#[aoc(day13, part2, Linear)]
+pub fn part2_linear(graph: &Graph) -> Option<BestRoutes> {
+    find_best_seating_arrangement(&add_guest(graph, "Self"), Table::Linear)
+}
 
-    find_best_seating_arrangement(&data.happiness, &people)
+#[aoc(day13, part2, GuestsFile)]
+pub fn part2_guests_file(graph: &Graph) -> Result<BestRoutes, GuestError> {
+    let graph = load_guests(graph, GUESTS_FILE)?;
+    find_best_seating_arrangement(&graph, Table::Circular).ok_or(GuestError::NoGuests)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_sample_input_p1() {
-        let test_data = "Alice would gain 54 happiness units by sitting next to Bob.\n\
+    const SAMPLE: &str = "Alice would gain 54 happiness units by sitting next to Bob.\n\
                                 Alice would lose 79 happiness units by sitting next to Carol.\n\
                                 Alice would lose 2 happiness units by sitting next to David.\n\
                                 Bob would gain 83 happiness units by sitting next to Alice.\n\
@@ -127,8 +175,76 @@ mod tests {
                                 David would lose 7 happiness units by sitting next to Bob.\n\
                                 David would gain 41 happiness units by sitting next to Carol.";
 
-        let gen = generate(test_data);
+    #[test]
+    fn test_sample_input_p1() {
+        let gen = generate(SAMPLE).expect("Sample guests are valid");
         let res = part1(&gen);
-        assert_eq!(res, 330);
+        assert_eq!(res.as_ref().map(|it| it.length), Some(330));
+
+        // each neighbour's happiness counts, so walk the table both ways
+        for names in res.iter().flat_map(|it| &it.routes) {
+            let mut route = names
+                .iter()
+                .filter_map(|name| gen.nodes().find(|it| gen.name(*it) == name))
+                .collect::<Vec<_>>();
+            let clockwise = gen.route_length(&route);
+            route.reverse();
+            let counter_clockwise = gen.route_length(&route);
+
+            assert_eq!(
+                clockwise.zip(counter_clockwise).map(|(a, b)| a + b),
+                Some(330)
+            );
+        }
+        assert_eq!(
+            res.map(|it| it.to_string()),
+            Some(String::from(
+                "Alice -> Bob -> Carol -> David -> Alice = 330"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sample_input_linear() {
+        let gen = generate(SAMPLE).expect("Sample guests are valid");
+        let res = part1_linear(&gen);
+        assert_eq!(
+            res.map(|it| it.to_string()),
+            Some(String::from("Alice -> Bob -> Carol -> David = 286"))
+        );
+    }
+
+    #[test]
+    fn test_extra_guests() {
+        let guests = "Me would gain 100 happiness units by sitting next to Carol.\n\
+                      Me would gain 100 happiness units by sitting next to David.\n\
+                      Nobody";
+
+        let gen = add_guests(&generate(SAMPLE).expect("Sample guests are valid"), guests)
+            .expect("Extra guests are valid");
+        let res = find_best_seating_arrangement(&gen, Table::Circular);
+        assert_eq!(res.map(|it| it.length), Some(390));
+    }
+
+    #[test]
+    fn test_invalid_guests() {
+        let gen = generate(SAMPLE).expect("Sample guests are valid");
+
+        let res = add_guests(&gen, "Nobody\n\nMe would like to sit next to Carol.");
+        assert_eq!(
+            res.map(|_| ()).map_err(|it| it.to_string()),
+            Err(String::from(
+                "line 3: \"Me would like to sit next to Carol.\" is neither a happiness rule nor a guest name"
+            ))
+        );
+
+        let res = generate(&format!("{SAMPLE}\nNobody"));
+        assert_eq!(
+            res.map(|_| ()).map_err(|it| it.to_string()),
+            Err(String::from("line 13: \"Nobody\" is not a happiness rule"))
+        );
+
+        let res = load_guests(&gen, "input/2015/day13_missing.txt");
+        assert!(matches!(res, Err(GuestError::Io(_))));
     }
 }
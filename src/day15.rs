@@ -1,9 +1,17 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::iproduct;
+use itertools::Itertools;
 use parse_display::{Display, FromStr};
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Formatter};
+use std::path::Path;
+use std::{fs, io};
 
-#[derive(Display, FromStr, Clone, Debug)]
+const TEASPOONS: i64 = 100;
+const PROPERTIES: [&str; 5] = ["capacity", "durability", "flavor", "texture", "calories"];
+const CONSTRAINTS_FILE: &str = "input/2015/day15_constraints.txt";
+
+#[derive(Display, FromStr, Clone, Debug, PartialEq, Eq)]
 #[display("capacity {capacity}, durability {durability}, flavor {flavor}, texture {texture}, calories {calories}")]
 pub struct IngredientData {
     capacity: i64,
@@ -28,114 +36,630 @@ pub fn generate(inp: &str) -> HashMap<String, IngredientData> {
         .collect()
 }
 
-fn calculate_score(
-    ingredients: &[(i64, &str)],
-    data: &HashMap<String, IngredientData>,
-    drop_if: fn(&[i64; 5]) -> bool,
-) -> Option<i64> {
-    let mut capacity = 0;
-    let mut durability = 0;
-    let mut flavor = 0;
-    let mut texture = 0;
-    let mut calories = 0;
-
-    for (num, key) in ingredients {
-        let ing_data = data.get(*key)?;
-        capacity += num * ing_data.capacity;
-        durability += num * ing_data.durability;
-        flavor += num * ing_data.flavor;
-        texture += num * ing_data.texture;
-        calories += num * ing_data.calories;
-    }
-
-    if drop_if(&[capacity, durability, flavor, texture, calories]) {
-        return Some(0);
+impl IngredientData {
+    const fn from_properties(properties: [i64; 5]) -> Self {
+        let [capacity, durability, flavor, texture, calories] = properties;
+
+        Self {
+            capacity,
+            durability,
+            flavor,
+            texture,
+            calories,
+        }
     }
 
-    Some(capacity * durability * flavor * texture)
+    const fn properties(&self) -> [i64; 5] {
+        [
+            self.capacity,
+            self.durability,
+            self.flavor,
+            self.texture,
+            self.calories,
+        ]
+    }
 }
 
-fn find_best_score(
-    data: &HashMap<String, IngredientData>,
-    pred: fn(&[i64; 5]) -> bool,
-) -> Option<i64> {
-    let mut result = 0;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    score: i64,
+    amounts: Vec<(String, i64)>,
+    totals: IngredientData,
+}
 
-    for (sp, pb, f) in iproduct!(0..=100, 0..=100, 0..=100) {
-        let s = 100 - sp - pb - f;
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let amounts = self
+            .amounts
+            .iter()
+            .map(|(name, amount)| format!("{name} {amount}"))
+            .join(", ");
 
-        if sp + pb + f + s != 100 {
-            continue;
+        write!(f, "{} = {amounts} ({})", self.score, self.totals)
+    }
+}
+
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
+enum Comparison {
+    #[display("<=")]
+    AtMost,
+
+    #[display(">=")]
+    AtLeast,
+
+    #[display("=")]
+    Exactly,
+}
+
+#[derive(Display, FromStr, Debug, Clone, PartialEq, Eq)]
+#[display("{name} {comparison} {value}")]
+struct Constraint {
+    name: String,
+    comparison: Comparison,
+    value: i64,
+}
+
+impl Constraint {
+    const fn bounds(&self) -> (i64, i64) {
+        match self.comparison {
+            Comparison::AtMost => (i64::MIN, self.value),
+            Comparison::AtLeast => (self.value, i64::MAX),
+            Comparison::Exactly => (self.value, self.value),
         }
+    }
+}
 
-        let ingredients = &[
-            (sp, "Sprinkles"),
-            (pb, "PeanutButter"),
-            (f, "Frosting"),
-            (s, "Sugar"),
-        ];
+#[derive(Debug)]
+pub enum RecipeError {
+    Io(io::Error),
+    BadClause(String),
+    UnknownName(String),
+    NoRecipe,
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "can't read constraints: {err}"),
+            Self::BadClause(clause) => write!(
+                f,
+                "can't parse constraint \"{clause}\", expected something like \"calories <= 500\""
+            ),
+            Self::UnknownName(name) => {
+                write!(f, "\"{name}\" is neither a property nor an ingredient")
+            }
+            Self::NoRecipe => write!(f, "no recipe satisfies the constraints"),
+        }
+    }
+}
+
+impl Error for RecipeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
-        let score = calculate_score(ingredients, data, pred)?;
-        result = result.max(score);
+impl From<io::Error> for RecipeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
     }
+}
 
-    Some(result)
+fn parse_constraints(inp: &str) -> Result<Vec<Constraint>, RecipeError> {
+    inp.split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+        .map(|it| {
+            it.parse()
+                .map_err(|_| RecipeError::BadClause(it.to_string()))
+        })
+        .collect()
 }
 
-fn part1_pred(elems: &[i64; 5]) -> bool {
-    elems.iter().take(4).any(|e| *e < 0)
+fn load_constraints(path: impl AsRef<Path>) -> Result<Vec<Constraint>, RecipeError> {
+    parse_constraints(&fs::read_to_string(path)?)
 }
 
-fn part2_pred(elems: &[i64; 5]) -> bool {
-    const CALORIES: Option<&i64> = Some(&500);
-    part1_pred(elems) || !elems.last().eq(&CALORIES)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Limits {
+    properties: [(i64, i64); 5],
+    amounts: Vec<(i64, i64)>,
+}
+
+impl Limits {
+    // a constraint either names a property or an ingredient, ignoring case
+    fn new(constraints: &[Constraint], names: &[&str]) -> Result<Self, RecipeError> {
+        let mut properties = [(i64::MIN, i64::MAX); 5];
+        let mut amounts = vec![(0, i64::MAX); names.len()];
+
+        for constraint in constraints {
+            let matches = |it: &&str| it.eq_ignore_ascii_case(&constraint.name);
+
+            let limit = if let Some(idx) = PROPERTIES.iter().position(matches) {
+                &mut properties[idx]
+            } else {
+                let idx = names
+                    .iter()
+                    .position(matches)
+                    .ok_or_else(|| RecipeError::UnknownName(constraint.name.clone()))?;
+                &mut amounts[idx]
+            };
+
+            let (lo, hi) = constraint.bounds();
+            *limit = (limit.0.max(lo), limit.1.min(hi));
+        }
+
+        Ok(Self {
+            properties,
+            amounts,
+        })
+    }
+
+    fn allows(&self, lowest: &[i64; 5], highest: &[i64; 5]) -> bool {
+        self.properties
+            .iter()
+            .zip(lowest.iter().zip(highest))
+            .all(|((lo, hi), (low, high))| high >= lo && low <= hi)
+    }
+}
+
+fn add_scaled(totals: &[i64; 5], amount: i64, properties: &[i64; 5]) -> [i64; 5] {
+    std::array::from_fn(|i| totals[i] + amount * properties[i])
+}
+
+// None when the recipe breaks a constraint, a negative property only scores 0
+fn calculate_score(totals: &[i64; 5], limits: &Limits) -> Option<i64> {
+    if !limits.allows(totals, totals) {
+        return None;
+    }
+
+    if totals.iter().take(4).any(|it| *it < 0) {
+        return Some(0);
+    }
+
+    Some(totals.iter().take(4).product())
+}
+
+struct RecipeSearch {
+    properties: Vec<[i64; 5]>,
+    suffix_min: Vec<[i64; 5]>,
+    suffix_max: Vec<[i64; 5]>,
+    // teaspoons the ingredients from an index on need at the very least
+    suffix_required: Vec<i64>,
+    limits: Limits,
+    amounts: Vec<i64>,
+    best: i64,
+    best_amounts: Option<Vec<i64>>,
+    best_totals: [i64; 5],
+}
+
+impl RecipeSearch {
+    fn new(properties: Vec<[i64; 5]>, limits: Limits) -> Self {
+        let mut suffix_min = properties.clone();
+        let mut suffix_max = properties.clone();
+
+        for idx in (0..properties.len().saturating_sub(1)).rev() {
+            for prop in 0..5 {
+                suffix_min[idx][prop] = suffix_min[idx][prop].min(suffix_min[idx + 1][prop]);
+                suffix_max[idx][prop] = suffix_max[idx][prop].max(suffix_max[idx + 1][prop]);
+            }
+        }
+
+        let mut suffix_required = vec![0; properties.len() + 1];
+        for (idx, (min_amount, _)) in limits.amounts.iter().enumerate().rev() {
+            suffix_required[idx] = suffix_required[idx + 1] + min_amount;
+        }
+
+        Self {
+            amounts: vec![0; properties.len()],
+            properties,
+            suffix_min,
+            suffix_max,
+            suffix_required,
+            limits,
+            best: 0,
+            best_amounts: None,
+            best_totals: [0; 5],
+        }
+    }
+
+    // Highest sum(objective[j] * amount[j]) over fractional splits of `remaining` among the
+    // ingredients from `idx` on, keeping only the limits on `prop`. A basic solution of this
+    // LP uses at most two ingredients, so trying every pair is exact.
+    #[allow(clippy::cast_precision_loss, clippy::float_cmp)]
+    fn max_linear(
+        &self,
+        idx: usize,
+        remaining: i64,
+        totals: &[i64; 5],
+        objective: &[f64],
+        prop: usize,
+    ) -> Option<f64> {
+        let (lo, hi) = self.limits.properties[prop];
+        if remaining == 0 {
+            return (lo..=hi).contains(&totals[prop]).then_some(0.0);
+        }
+
+        // average amount of `prop` per remaining teaspoon, widened against rounding
+        let per_teaspoon = |limit: i64| (limit as f64 - totals[prop] as f64) / remaining as f64;
+        let (lo, hi) = (per_teaspoon(lo) - 1e-9, per_teaspoon(hi) + 1e-9);
+
+        let mut best: Option<f64> = None;
+
+        for j in idx..self.properties.len() {
+            for k in j..self.properties.len() {
+                let (aj, ak) = (
+                    self.properties[j][prop] as f64,
+                    self.properties[k][prop] as f64,
+                );
+                let (oj, ok) = (objective[j - idx], objective[k - idx]);
+
+                // t teaspoons of j per (1 - t) of k
+                let (t_lo, t_hi) = if aj == ak {
+                    if !(lo..=hi).contains(&aj) {
+                        continue;
+                    }
+
+                    (0.0, 1.0)
+                } else {
+                    let (a, b) = ((lo - ak) / (aj - ak), (hi - ak) / (aj - ak));
+                    (a.min(b).max(0.0), a.max(b).min(1.0))
+                };
+
+                if t_lo > t_hi {
+                    continue;
+                }
+
+                let value = (oj - ok).mul_add(t_lo, ok).max((oj - ok).mul_add(t_hi, ok));
+                best = Some(best.map_or(value, |it| it.max(value)));
+            }
+        }
+
+        best.map(|it| it * remaining as f64)
+    }
+
+    // For positive totals AM-GM gives prod(p) <= prod(w) * (sum(p / w) / 4)^4 for any positive
+    // weights w, and the sum is linear so its best completion is easy to find.
+    #[allow(clippy::cast_precision_loss)]
+    fn am_gm_bound(
+        &self,
+        idx: usize,
+        remaining: i64,
+        totals: &[i64; 5],
+        weights: &[i64; 5],
+    ) -> f64 {
+        let scaled_sum = |props: &[i64; 5]| {
+            (0..4)
+                .map(|i| props[i] as f64 / weights[i] as f64)
+                .sum::<f64>()
+        };
+
+        let objective = self.properties[idx..].iter().map(scaled_sum).collect_vec();
+
+        let mut best_completion =
+            remaining as f64 * objective.iter().copied().fold(f64::MIN, f64::max);
+
+        // every limited property on its own gives a (possibly) tighter relaxation
+        for prop in (0..5).filter(|it| self.limits.properties[*it] != (i64::MIN, i64::MAX)) {
+            match self.max_linear(idx, remaining, totals, &objective, prop) {
+                Some(value) => best_completion = best_completion.min(value),
+                None => return 0.0,
+            }
+        }
+
+        let sum = scaled_sum(totals) + best_completion;
+        let product = weights.iter().take(4).map(|it| *it as f64).product::<f64>();
+
+        product * (sum / 4.0).max(0.0).powi(4)
+    }
+
+    // negative totals are always dropped, so only completions with positive totals matter
+    #[allow(clippy::cast_precision_loss)]
+    fn score_bound(
+        &self,
+        idx: usize,
+        remaining: i64,
+        totals: &[i64; 5],
+        highest: &[i64; 5],
+    ) -> f64 {
+        if highest.iter().take(4).any(|it| *it <= 0) {
+            return 0.0;
+        }
+
+        let capped = highest.iter().take(4).map(|it| *it as f64).product::<f64>();
+        let mut bound = capped.min(self.am_gm_bound(idx, remaining, totals, highest));
+
+        // weighting by the best recipe so far is tight close to the optimum
+        if self.best_totals.iter().take(4).all(|it| *it > 0) {
+            bound = bound.min(self.am_gm_bound(idx, remaining, totals, &self.best_totals));
+        }
+
+        bound
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn search(&mut self, idx: usize, remaining: i64, totals: [i64; 5]) {
+        let (min_amount, max_amount) = self.limits.amounts[idx];
+
+        // the last ingredient takes whatever is left of the budget
+        if idx + 1 == self.properties.len() {
+            if (min_amount..=max_amount).contains(&remaining) {
+                self.amounts[idx] = remaining;
+
+                let totals = add_scaled(&totals, remaining, &self.properties[idx]);
+                let score = calculate_score(&totals, &self.limits);
+                let improves = |score| self.best_amounts.is_none() || score > self.best;
+                if let Some(score) = score.filter(|it| improves(*it)) {
+                    self.best = score;
+                    self.best_amounts = Some(self.amounts.clone());
+                    self.best_totals = totals;
+                }
+            }
+
+            return;
+        }
+
+        let lowest = add_scaled(&totals, remaining, &self.suffix_min[idx]);
+        let highest = add_scaled(&totals, remaining, &self.suffix_max[idx]);
+
+        if !self.limits.allows(&lowest, &highest) {
+            return;
+        }
+
+        // inflate slightly so rounding can never prune the optimum
+        let bound = self.score_bound(idx, remaining, &totals, &highest) * (1.0 + 1e-9);
+        if self.best_amounts.is_some() && bound <= self.best as f64 {
+            return;
+        }
+
+        let max_amount = max_amount.min(remaining - self.suffix_required[idx + 1]);
+
+        for amount in (min_amount..=max_amount).rev() {
+            self.amounts[idx] = amount;
+
+            let totals = add_scaled(&totals, amount, &self.properties[idx]);
+            self.search(idx + 1, remaining - amount, totals);
+        }
+    }
+}
+
+fn find_best_recipe(
+    data: &HashMap<String, IngredientData>,
+    teaspoons: i64,
+    constraints: &[Constraint],
+) -> Result<Recipe, RecipeError> {
+    let names = data.keys().map(String::as_str).sorted().collect_vec();
+    let properties = names.iter().map(|it| data[*it].properties()).collect_vec();
+    let limits = Limits::new(constraints, &names)?;
+
+    if names.is_empty() {
+        return Err(RecipeError::NoRecipe);
+    }
+
+    let mut search = RecipeSearch::new(properties, limits);
+    search.search(0, teaspoons, [0; 5]);
+
+    let amounts = names
+        .into_iter()
+        .map(String::from)
+        .zip(search.best_amounts.ok_or(RecipeError::NoRecipe)?)
+        .collect();
+
+    Ok(Recipe {
+        score: search.best,
+        amounts,
+        totals: IngredientData::from_properties(search.best_totals),
+    })
 }
 
 #[aoc(day15, part1)]
-pub fn part1(data: &HashMap<String, IngredientData>) -> Option<i64> {
-    find_best_score(data, part1_pred)
+pub fn part1(data: &HashMap<String, IngredientData>) -> Result<Recipe, RecipeError> {
+    find_best_recipe(data, TEASPOONS, &[])
 }
 
 #[aoc(day15, part2)]
-pub fn part2(data: &HashMap<String, IngredientData>) -> Option<i64> {
-    find_best_score(data, part2_pred)
+pub fn part2(data: &HashMap<String, IngredientData>) -> Result<Recipe, RecipeError> {
+    let constraints = parse_constraints("calories = 500")?;
+    find_best_recipe(data, TEASPOONS, &constraints)
+}
+
+#[aoc(day15, part2, ConstraintsFile)]
+pub fn part2_constraints_file(
+    data: &HashMap<String, IngredientData>,
+) -> Result<Recipe, RecipeError> {
+    let constraints = load_constraints(CONSTRAINTS_FILE)?;
+    find_best_recipe(data, TEASPOONS, &constraints)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_test_score(
-        gen: &HashMap<String, IngredientData>,
-        pred: fn(&[i64; 5]) -> bool,
-    ) -> Option<i64> {
-        (0..=100)
-            .filter_map(|bs| {
-                let c = 100 - bs;
-                calculate_score(&[(bs, "Butterscotch"), (c, "Cinnamon")], gen, pred)
+    #[test]
+    fn test_sample_input_p1() {
+        let test_data = "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8\n\
+                                Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3";
+
+        let gen = generate(test_data);
+
+        let res = part1(&gen).ok();
+        assert_eq!(res.as_ref().map(|it| it.score), Some(62_842_880));
+        assert_eq!(
+            res,
+            Some(Recipe {
+                score: 62_842_880,
+                amounts: vec![
+                    (String::from("Butterscotch"), 44),
+                    (String::from("Cinnamon"), 56)
+                ],
+                totals: IngredientData::from_properties([68, 80, 152, 76, 520]),
             })
-            .max()
+        );
     }
 
     #[test]
-    fn test_sample_input_p1() {
+    fn test_sample_input_p2() {
         let test_data = "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8\n\
                                 Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3";
 
         let gen = generate(test_data);
 
-        let res = get_test_score(&gen, part1_pred);
-        assert_eq!(res, Some(62_842_880));
+        let res = part2(&gen);
+        assert_eq!(
+            res.map(|it| it.to_string()).ok(),
+            Some(String::from(
+                "57600000 = Butterscotch 40, Cinnamon 60 \
+                 (capacity 80, durability 100, flavor 120, texture 60, calories 500)"
+            ))
+        );
     }
 
     #[test]
-    fn test_sample_input_p2() {
+    fn test_teaspoon_budget() {
+        let test_data = "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8\n\
+                                Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3";
+
+        let gen = generate(test_data);
+
+        let res = find_best_recipe(&gen, 10, &[]);
+        assert_eq!(res.map(|it| it.score).ok(), Some(5_760));
+    }
+
+    #[test]
+    fn test_parse_constraints() {
+        let res = parse_constraints("calories = 500, sugar <= 20, texture >= 1");
+        assert_eq!(
+            res.ok(),
+            Some(vec![
+                Constraint {
+                    name: String::from("calories"),
+                    comparison: Comparison::Exactly,
+                    value: 500,
+                },
+                Constraint {
+                    name: String::from("sugar"),
+                    comparison: Comparison::AtMost,
+                    value: 20,
+                },
+                Constraint {
+                    name: String::from("texture"),
+                    comparison: Comparison::AtLeast,
+                    value: 1,
+                },
+            ])
+        );
+
+        assert_eq!(parse_constraints("").ok(), Some(vec![]));
+        assert_eq!(
+            parse_constraints("sugar <= 20, calories == 500").map_err(|it| it.to_string()),
+            Err(String::from(
+                "can't parse constraint \"calories == 500\", expected something like \"calories <= 500\""
+            ))
+        );
+
+        let res = load_constraints("input/2015/day15_missing.txt");
+        assert!(matches!(res, Err(RecipeError::Io(_))));
+    }
+
+    #[test]
+    fn test_ingredient_constraints() {
         let test_data = "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8\n\
                                 Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3";
 
         let gen = generate(test_data);
 
-        let res = get_test_score(&gen, part2_pred);
-        assert_eq!(res, Some(57_600_000));
+        let constraints = parse_constraints("butterscotch >= 50, Cinnamon >= 45")
+            .expect("Constraints are well formed");
+        let res = find_best_recipe(&gen, TEASPOONS, &constraints);
+        assert_eq!(res.map(|it| it.score).ok(), Some(50_000_000));
+
+        let constraints = parse_constraints("nutmeg <= 20").expect("Constraints are well formed");
+        let res = find_best_recipe(&gen, TEASPOONS, &constraints);
+        assert_eq!(
+            res.map_err(|it| it.to_string()),
+            Err(String::from(
+                "\"nutmeg\" is neither a property nor an ingredient"
+            ))
+        );
+
+        let constraints = parse_constraints("calories = 7").expect("Constraints are well formed");
+        let res = find_best_recipe(&gen, TEASPOONS, &constraints);
+        assert!(matches!(res, Err(RecipeError::NoRecipe)));
+    }
+
+    #[test]
+    fn test_zero_score() {
+        let gen = generate("Water: capacity 0, durability 0, flavor 0, texture 0, calories 0");
+
+        let res = find_best_recipe(&gen, TEASPOONS, &[]);
+        assert_eq!(res.map(|it| it.score).ok(), Some(0));
+
+        let constraints = parse_constraints("calories = 0").expect("Constraints are well formed");
+        let res = find_best_recipe(&gen, TEASPOONS, &constraints);
+        assert_eq!(
+            res.map(|it| it.to_string()).ok(),
+            Some(String::from(
+                "0 = Water 100 (capacity 0, durability 0, flavor 0, texture 0, calories 0)"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let test_data = "A: capacity 4, durability -2, flavor 0, texture 0, calories 5\n\
+                         B: capacity 0, durability 5, flavor -1, texture 0, calories 8\n\
+                         C: capacity -1, durability 0, flavor 5, texture 0, calories 6\n\
+                         D: capacity 0, durability 0, flavor -2, texture 2, calories 1";
+
+        let gen = generate(test_data);
+        let names = ["A", "B", "C", "D"];
+        let properties = names.map(|it| gen[it].properties());
+
+        let cases = [
+            (30, ""),
+            (100, "calories = 500"),
+            (60, "calories <= 300, durability >= 20, b <= 10"),
+            (60, "a = 12, c >= 20, flavor <= 40"),
+        ];
+
+        for (teaspoons, constraints) in cases {
+            let constraints = parse_constraints(constraints).expect("Constraints are well formed");
+            let limits = Limits::new(&constraints, &names).expect("Names are known");
+
+            let mut expected = None;
+            for (a, b, c) in itertools::iproduct!(0..=teaspoons, 0..=teaspoons, 0..=teaspoons) {
+                if a + b + c > teaspoons {
+                    continue;
+                }
+
+                let amounts = [a, b, c, teaspoons - a - b - c];
+                let allowed = amounts
+                    .iter()
+                    .zip(&limits.amounts)
+                    .all(|(amount, (lo, hi))| (lo..=hi).contains(&amount));
+
+                if !allowed {
+                    continue;
+                }
+
+                let totals = amounts
+                    .iter()
+                    .zip(&properties)
+                    .fold([0; 5], |acc, (amount, props)| {
+                        add_scaled(&acc, *amount, props)
+                    });
+
+                expected = expected.max(calculate_score(&totals, &limits));
+            }
+
+            let res = find_best_recipe(&gen, teaspoons, &constraints);
+            assert_eq!(
+                res.map(|it| it.score).map_err(|it| it.to_string()),
+                expected.ok_or_else(|| RecipeError::NoRecipe.to_string())
+            );
+        }
     }
 }
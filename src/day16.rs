@@ -1,68 +1,127 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use regex::{Error, Regex};
-use std::cmp::Ordering;
-use std::cmp::Ordering::{Equal, Greater, Less};
+use itertools::Itertools;
+use parse_display::{Display, FromStr};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Formatter};
 use std::str::FromStr;
 
+const TOP_CANDIDATES: usize = 3;
+const TICKER_TAPE: &str = "children: 3\n\
+                           cats: 7\n\
+                           samoyeds: 2\n\
+                           pomeranians: 3\n\
+                           akitas: 0\n\
+                           vizslas: 0\n\
+                           goldfish: 5\n\
+                           trees: 3\n\
+                           cars: 2\n\
+                           perfumes: 1";
+
+// the retroencabulator reports ranges for some compounds
+const CALIBRATION: &str = "cats: >7\n\
+                           trees: >3\n\
+                           pomeranians: <3\n\
+                           goldfish: <5";
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct Sue {
     idx: usize,
-    children: Option<usize>,
-    cats: Option<usize>,
-    samoyeds: Option<usize>,
-    pomeranians: Option<usize>,
-    akitas: Option<usize>,
-    vizslas: Option<usize>,
-    goldfish: Option<usize>,
-    trees: Option<usize>,
-    cars: Option<usize>,
-    perfumes: Option<usize>,
-}
-
-fn get_regex(s: &str) -> Result<Regex, Error> {
-    let pattern = format!("{s}: ([0-9]+)");
-    Regex::new(&pattern)
-}
-
-fn get_item(s: &str, item: &str) -> Option<usize> {
-    let re = get_regex(item).ok()?;
-    if re.is_match(s) {
-        re.captures(s)
-            .and_then(|it| it.get(1))
-            .and_then(|it| it.as_str().parse().ok())
-    } else {
-        None
+    items: HashMap<String, usize>,
+}
+
+#[derive(Display, FromStr, PartialEq, Eq, Debug, Copy, Clone)]
+enum Rule {
+    #[display(">{0}")]
+    MoreThan(usize),
+
+    #[display("<{0}")]
+    FewerThan(usize),
+
+    #[display("{0}..{1}")]
+    Between(usize, usize),
+
+    #[display("{0}")]
+    Exactly(usize),
+}
+
+impl Rule {
+    const fn matches(self, value: usize) -> bool {
+        match self {
+            Self::MoreThan(min) => value > min,
+            Self::FewerThan(max) => value < max,
+            Self::Between(min, max) => min <= value && value <= max,
+            Self::Exactly(expected) => value == expected,
+        }
     }
 }
 
-fn get_idx(s: &str) -> Option<usize> {
-    let re = Regex::new("Sue ([0-9]+)").ok()?;
-    re.captures(s)
-        .and_then(|it| it.get(1))
-        .and_then(|it| it.as_str().parse().ok())
+#[derive(Display, FromStr, PartialEq, Eq, Debug, Clone)]
+#[display("{name}: {rule}")]
+struct Reading {
+    name: String,
+    rule: Rule,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReadingError {
+    Unrecognized { line: usize, text: String },
+    NoMatch,
+}
+
+impl fmt::Display for ReadingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unrecognized { line, text } => {
+                write!(f, "line {line}: \"{text}\" is not a reading")
+            }
+            Self::NoMatch => write!(f, "no Sue matches the readings"),
+        }
+    }
+}
+
+impl Error for ReadingError {}
+
+fn parse_readings(inp: &str) -> Result<HashMap<String, Rule>, ReadingError> {
+    inp.lines()
+        .map(str::trim)
+        .enumerate()
+        .map(|(idx, line)| {
+            line.parse::<Reading>()
+                .map(|it| (it.name, it.rule))
+                .map_err(|_| ReadingError::Unrecognized {
+                    line: idx + 1,
+                    text: line.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn parse_item(s: &str) -> Option<(String, usize)> {
+    let (name, value) = s.split_once(": ")?;
+    Some((name.to_string(), value.parse().ok()?))
 }
 
 impl FromStr for Sue {
     type Err = ();
 
+    // "Sue <idx>: <name>: <value>, <name>: <value>, ..."
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let Some(idx) = get_idx(s) else {
-            return Err(());
-        };
+        let (head, items) = s.split_once(": ").ok_or(())?;
 
-        Ok(Self {
-            idx,
-            akitas: get_item(s, "akitas"),
-            cars: get_item(s, "cars"),
-            cats: get_item(s, "cats"),
-            children: get_item(s, "children"),
-            goldfish: get_item(s, "goldfish"),
-            perfumes: get_item(s, "perfumes"),
-            pomeranians: get_item(s, "pomeranians"),
-            samoyeds: get_item(s, "samoyeds"),
-            trees: get_item(s, "trees"),
-            vizslas: get_item(s, "vizslas"),
-        })
+        let idx = head
+            .strip_prefix("Sue ")
+            .and_then(|it| it.parse().ok())
+            .ok_or(())?;
+
+        let items = items
+            .split(", ")
+            .map(parse_item)
+            .collect::<Option<_>>()
+            .ok_or(())?;
+
+        Ok(Self { idx, items })
     }
 }
 
@@ -73,27 +132,16 @@ pub fn generate(inp: &str) -> Vec<Sue> {
         .collect()
 }
 
-fn matches_items(sue: &Sue, cat_tree_ord: Ordering, pomeranian_fish_ord: Ordering) -> bool {
-    sue.akitas.map_or(true, |a| a == 0)
-        && sue.cars.map_or(true, |c| c == 2)
-        && sue.cats.map_or(true, |c| c.cmp(&7) == cat_tree_ord)
-        && sue.children.map_or(true, |c| c == 3)
-        && sue
-            .goldfish
-            .map_or(true, |g| g.cmp(&5) == pomeranian_fish_ord)
-        && sue.perfumes.map_or(true, |p| p == 1)
-        && sue
-            .pomeranians
-            .map_or(true, |p| p.cmp(&3) == pomeranian_fish_ord)
-        && sue.samoyeds.map_or(true, |s| s == 2)
-        && sue.trees.map_or(true, |t| t.cmp(&3) == cat_tree_ord)
-        && sue.vizslas.map_or(true, |v| v == 0)
+// attributes missing from either side are unknown and can't rule a Sue out
+fn matches_items(sue: &Sue, readings: &HashMap<String, Rule>) -> bool {
+    sue.items
+        .iter()
+        .all(|(name, value)| readings.get(name).is_none_or(|rule| rule.matches(*value)))
 }
 
-#[aoc(day16, part1)]
-pub fn part1(sues: &[Sue]) -> Option<usize> {
+fn find_sue(sues: &[Sue], readings: &HashMap<String, Rule>) -> Option<usize> {
     sues.iter().find_map(|it| {
-        if matches_items(it, Equal, Equal) {
+        if matches_items(it, readings) {
             Some(it.idx)
         } else {
             None
@@ -101,15 +149,96 @@ pub fn part1(sues: &[Sue]) -> Option<usize> {
     })
 }
 
-#[aoc(day16, part2)]
-pub fn part2(sues: &[Sue]) -> Option<usize> {
-    sues.iter().find_map(|it| {
-        if matches_items(it, Greater, Less) {
-            Some(it.idx)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    idx: usize,
+    agreeing: usize,
+    conflicts: Vec<String>,
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Sue {}: {} agreeing", self.idx, self.agreeing)?;
+
+        if self.conflicts.is_empty() {
+            write!(f, ", no conflicts")
         } else {
-            None
+            write!(f, ", conflicts: {}", self.conflicts.join(", "))
         }
-    })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranking(Vec<Candidate>);
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.0.iter().map(ToString::to_string).join("\n");
+        write!(f, "{lines}")
+    }
+}
+
+fn score_sue(sue: &Sue, readings: &HashMap<String, Rule>) -> Candidate {
+    let mut agreeing = 0;
+    let mut conflicts = Vec::new();
+
+    for (name, value) in &sue.items {
+        match readings.get(name) {
+            Some(rule) if rule.matches(*value) => agreeing += 1,
+            Some(_) => conflicts.push(name.clone()),
+            None => {}
+        }
+    }
+
+    conflicts.sort_unstable();
+
+    Candidate {
+        idx: sue.idx,
+        agreeing,
+        conflicts,
+    }
+}
+
+// fewest conflicts first, then most agreeing attributes
+fn rank_sues(sues: &[Sue], readings: &HashMap<String, Rule>, top: usize) -> Ranking {
+    let ranking = sues
+        .iter()
+        .map(|it| score_sue(it, readings))
+        .sorted_by_key(|it| (it.conflicts.len(), Reverse(it.agreeing), it.idx))
+        .take(top)
+        .collect();
+
+    Ranking(ranking)
+}
+
+fn part2_readings() -> Result<HashMap<String, Rule>, ReadingError> {
+    let mut readings = parse_readings(TICKER_TAPE)?;
+    readings.extend(parse_readings(CALIBRATION)?);
+    Ok(readings)
+}
+
+#[aoc(day16, part1)]
+pub fn part1(sues: &[Sue]) -> Result<usize, ReadingError> {
+    find_sue(sues, &parse_readings(TICKER_TAPE)?).ok_or(ReadingError::NoMatch)
+}
+
+#[aoc(day16, part1, Ranked)]
+pub fn part1_ranked(sues: &[Sue]) -> Result<Ranking, ReadingError> {
+    Ok(rank_sues(
+        sues,
+        &parse_readings(TICKER_TAPE)?,
+        TOP_CANDIDATES,
+    ))
+}
+
+#[aoc(day16, part2)]
+pub fn part2(sues: &[Sue]) -> Result<usize, ReadingError> {
+    find_sue(sues, &part2_readings()?).ok_or(ReadingError::NoMatch)
+}
+
+#[aoc(day16, part2, Ranked)]
+pub fn part2_ranked(sues: &[Sue]) -> Result<Ranking, ReadingError> {
+    Ok(rank_sues(sues, &part2_readings()?, TOP_CANDIDATES))
 }
 
 #[cfg(test)]
@@ -128,17 +257,157 @@ mod tests {
             sue,
             Some(&Sue {
                 idx: 1,
-                children: None,
-                cats: None,
-                goldfish: Some(6),
-                trees: Some(9),
-                cars: None,
-                akitas: Some(0),
-                pomeranians: None,
-                samoyeds: None,
-                vizslas: None,
-                perfumes: None,
+                items: HashMap::from([
+                    (String::from("goldfish"), 6),
+                    (String::from("trees"), 9),
+                    (String::from("akitas"), 0),
+                ]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_readings() {
+        let readings = parse_readings("cats: >7\nchildren: 3\nstars: 2..4\npomeranians: <3");
+
+        assert_eq!(
+            readings,
+            Ok(HashMap::from([
+                (String::from("cats"), Rule::MoreThan(7)),
+                (String::from("children"), Rule::Exactly(3)),
+                (String::from("stars"), Rule::Between(2, 4)),
+                (String::from("pomeranians"), Rule::FewerThan(3)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_malformed_reading() {
+        let res = parse_readings("children: 3\ncats: > 7");
+        assert_eq!(
+            res,
+            Err(ReadingError::Unrecognized {
+                line: 2,
+                text: String::from("cats: > 7"),
             })
         );
+        assert_eq!(
+            res.map_err(|it| it.to_string()),
+            Err(String::from("line 2: \"cats: > 7\" is not a reading"))
+        );
+    }
+
+    #[test]
+    fn test_new_compound() {
+        let sues = generate(
+            "Sue 1: stars: 1, cats: 7\n\
+             Sue 2: stars: 5, cats: 7\n\
+             Sue 3: stars: 3, cats: 7",
+        );
+
+        let res = find_sue(
+            &sues,
+            &parse_readings("stars: 2..4\ncats: 7").expect("Readings are valid"),
+        );
+        assert_eq!(res, Some(3));
+    }
+
+    #[test]
+    fn test_ranking() {
+        let sues = generate(
+            "Sue 1: cats: 7, trees: 1, cars: 9\n\
+             Sue 2: cats: 7, trees: 3, cars: 9\n\
+             Sue 3: cats: 6, trees: 2, cars: 1",
+        );
+
+        let readings = parse_readings(TICKER_TAPE).expect("Readings are valid");
+        assert_eq!(find_sue(&sues, &readings), None);
+
+        let res = rank_sues(&sues, &readings, 2);
+        assert_eq!(
+            res,
+            Ranking(vec![
+                Candidate {
+                    idx: 2,
+                    agreeing: 2,
+                    conflicts: vec![String::from("cars")],
+                },
+                Candidate {
+                    idx: 1,
+                    agreeing: 1,
+                    conflicts: vec![String::from("cars"), String::from("trees")],
+                },
+            ])
+        );
+
+        assert_eq!(
+            res.to_string(),
+            "Sue 2: 2 agreeing, conflicts: cars\n\
+             Sue 1: 1 agreeing, conflicts: cars, trees"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert_eq!("Sue x: cats: 7".parse::<Sue>(), Err(()));
+        assert_eq!("Sue 1: cats 7".parse::<Sue>(), Err(()));
+        assert_eq!("Sue 1 cats: 7".parse::<Sue>(), Err(()));
+    }
+
+    // the previous parser compiled one regex per attribute and line
+    fn parse_with_regexes(s: &str) -> Option<Sue> {
+        let get = |pattern: &str| {
+            regex::Regex::new(pattern)
+                .ok()?
+                .captures(s)?
+                .get(1)?
+                .as_str()
+                .parse()
+                .ok()
+        };
+
+        let idx = get("Sue ([0-9]+)")?;
+        let items = [
+            "children",
+            "cats",
+            "samoyeds",
+            "pomeranians",
+            "akitas",
+            "vizslas",
+            "goldfish",
+            "trees",
+            "cars",
+            "perfumes",
+        ]
+        .into_iter()
+        .filter_map(|name| Some((name.to_string(), get(&format!("{name}: ([0-9]+)"))?)))
+        .collect();
+
+        Some(Sue { idx, items })
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release bench_parse -- --ignored --nocapture`"]
+    fn bench_parse() {
+        let inp = std::fs::read_to_string("input/2015/day16.txt")
+            .expect("Benchmark needs input/2015/day16.txt");
+        let runs = 20;
+
+        let start = std::time::Instant::now();
+        let mut expected = Vec::new();
+        for _ in 0..runs {
+            expected = inp.lines().filter_map(parse_with_regexes).collect();
+        }
+        let with_regexes = start.elapsed() / runs;
+
+        let start = std::time::Instant::now();
+        let mut res = Vec::new();
+        for _ in 0..runs {
+            res = generate(&inp);
+        }
+        let single_pass = start.elapsed() / runs;
+
+        println!("regex per attribute: {with_regexes:?}, single pass: {single_pass:?}");
+        assert_eq!(res, expected);
     }
 }
@@ -1,46 +1,145 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use parse_display::{Display, FromStr};
+use std::collections::BTreeMap;
+use std::fmt::{self, Formatter};
 
+const DEFAULT_EGGNOG: usize = 150;
+
+#[derive(Display, FromStr, Debug, Copy, Clone, PartialEq, Eq)]
+enum Line {
+    #[display("eggnog: {0}")]
+    Eggnog(usize),
+
+    #[display("{0}")]
+    Container(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+    containers: Vec<usize>,
+    eggnog: usize,
+}
+
+// an optional "eggnog: <liters>" line overrides the volume to store
 #[aoc_generator(day17)]
-pub fn generate(inp: &str) -> Vec<usize> {
-    inp.lines().filter_map(|it| it.parse().ok()).collect()
+pub fn generate(inp: &str) -> Storage {
+    let mut storage = Storage {
+        containers: Vec::new(),
+        eggnog: DEFAULT_EGGNOG,
+    };
+
+    for line in inp.lines().filter_map(|it| it.trim().parse::<Line>().ok()) {
+        match line {
+            Line::Eggnog(liters) => storage.eggnog = liters,
+            Line::Container(size) => storage.containers.push(size),
+        }
+    }
+
+    storage
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct ContainerCounts {
+    combinations: usize,
+    min_containers: Option<usize>,
+    min_combinations: usize,
 }
 
-fn get_combinations(nums: &[usize], target: usize) -> Vec<Vec<usize>> {
-    (1..nums.len())
-        .filter_map(|len| {
-            let matching = nums
-                .iter()
-                .copied()
-                .combinations(len)
-                .filter(|comb| comb.iter().sum::<usize>() == target)
-                .collect_vec();
-            if matching.is_empty() {
-                None
-            } else {
-                Some(matching)
+fn count_combinations(nums: &[usize], target: usize) -> ContainerCounts {
+    // ways[len][volume] = number of container sets with `len` containers holding `volume`
+    let mut ways = vec![vec![0usize; target + 1]; nums.len() + 1];
+    ways[0][0] = 1;
+
+    for (idx, &size) in nums.iter().enumerate() {
+        for len in (0..=idx).rev() {
+            for volume in (size..=target).rev() {
+                ways[len + 1][volume] += ways[len][volume - size];
             }
-        })
-        .flatten()
-        .collect()
+        }
+    }
+
+    let by_len = ways.iter().skip(1).map(|it| it[target]);
+    let min_containers = by_len.clone().position(|it| it > 0).map(|it| it + 1);
+
+    ContainerCounts {
+        combinations: by_len.sum(),
+        min_containers,
+        min_combinations: min_containers.map_or(0, |it| ways[it][target]),
+    }
 }
 
-#[aoc(day17, part1)]
-pub fn part1(nums: &[usize]) -> usize {
-    get_combinations(nums, 150).len()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerSets {
+    sizes: Vec<usize>,
+    // container indices of every valid set, grouped by the number of containers
+    by_len: BTreeMap<usize, Vec<Vec<usize>>>,
+}
+
+impl fmt::Display for ContainerSets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (len, sets) in &self.by_len {
+            writeln!(f, "{len} containers ({} sets):", sets.len())?;
+
+            for set in sets {
+                let indices = set.iter().join(", ");
+                let sizes = set.iter().map(|it| self.sizes[*it]).join(" + ");
+                writeln!(f, "  {indices} ({sizes})")?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
-fn count_min_containers(combs: &mut [Vec<usize>]) -> Option<usize> {
-    combs.sort_by_key(Vec::len);
+fn collect_sets(
+    sizes: &[usize],
+    start: usize,
+    remaining: usize,
+    current: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    if remaining == 0 && !current.is_empty() {
+        out.push(current.clone());
+    }
 
-    let len = combs.first().map(Vec::len)?;
-    combs.iter().counts_by(Vec::len).get(&len).copied()
+    for idx in start..sizes.len() {
+        if sizes[idx] <= remaining {
+            current.push(idx);
+            collect_sets(sizes, idx + 1, remaining - sizes[idx], current, out);
+            current.pop();
+        }
+    }
+}
+
+fn enumerate_sets(sizes: &[usize], target: usize) -> ContainerSets {
+    let mut sets = Vec::new();
+    collect_sets(sizes, 0, target, &mut Vec::new(), &mut sets);
+
+    ContainerSets {
+        sizes: sizes.to_vec(),
+        by_len: sets
+            .into_iter()
+            .into_group_map_by(Vec::len)
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[aoc(day17, part1)]
+pub fn part1(storage: &Storage) -> usize {
+    count_combinations(&storage.containers, storage.eggnog).combinations
+}
+
+#[aoc(day17, part1, Enumerate)]
+pub fn part1_enumerate(storage: &Storage) -> ContainerSets {
+    enumerate_sets(&storage.containers, storage.eggnog)
 }
 
 #[aoc(day17, part2)]
-pub fn part2(nums: &[usize]) -> Option<usize> {
-    let mut combs = get_combinations(nums, 150);
-    count_min_containers(&mut combs)
+pub fn part2(storage: &Storage) -> Option<usize> {
+    let counts = count_combinations(&storage.containers, storage.eggnog);
+    counts.min_containers.map(|_| counts.min_combinations)
 }
 
 #[cfg(test)]
@@ -50,7 +149,7 @@ mod tests {
     #[test]
     fn test_sample_input_p1() {
         let inp = vec![20, 15, 10, 5, 5];
-        let res = get_combinations(&inp, 25).len();
+        let res = count_combinations(&inp, 25).combinations;
 
         assert_eq!(res, 4);
     }
@@ -58,9 +157,81 @@ mod tests {
     #[test]
     fn test_sample_input_p2() {
         let inp = vec![20, 15, 10, 5, 5];
-        let mut combs = get_combinations(&inp, 25);
-        let res = count_min_containers(&mut combs);
+        let res = count_combinations(&inp, 25);
+
+        assert_eq!(res.min_containers, Some(2));
+        assert_eq!(res.min_combinations, 3);
+    }
+
+    #[test]
+    fn test_all_containers() {
+        let inp = vec![5, 5, 10];
+        let res = count_combinations(&inp, 20);
+
+        assert_eq!(
+            res,
+            ContainerCounts {
+                combinations: 1,
+                min_containers: Some(3),
+                min_combinations: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_combination() {
+        let inp = vec![20, 15];
+        let res = count_combinations(&inp, 25);
+
+        assert_eq!(
+            res,
+            ContainerCounts {
+                combinations: 0,
+                min_containers: None,
+                min_combinations: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_eggnog() {
+        let res = generate("eggnog: 25\n20\n15\n10\n5\n5");
+        assert_eq!(
+            res,
+            Storage {
+                containers: vec![20, 15, 10, 5, 5],
+                eggnog: 25,
+            }
+        );
+
+        assert_eq!(part1(&res), 4);
+        assert_eq!(part2(&res), Some(3));
+        assert_eq!(generate("20\n15").eggnog, DEFAULT_EGGNOG);
+    }
+
+    #[test]
+    fn test_enumerate_sets() {
+        let inp = vec![20, 15, 10, 5, 5];
+        let res = enumerate_sets(&inp, 25);
+
+        assert_eq!(
+            res.by_len,
+            BTreeMap::from([
+                (2, vec![vec![0, 3], vec![0, 4], vec![1, 2]]),
+                (3, vec![vec![1, 3, 4]]),
+            ])
+        );
 
-        assert_eq!(res, Some(3));
+        assert_eq!(
+            res.to_string().lines().collect_vec(),
+            vec![
+                "2 containers (3 sets):",
+                "  0, 3 (20 + 5)",
+                "  0, 4 (20 + 5)",
+                "  1, 2 (15 + 10)",
+                "3 containers (1 sets):",
+                "  1, 3, 4 (15 + 5 + 5)",
+            ]
+        );
     }
 }
@@ -1,110 +1,172 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::{iproduct, Itertools};
+use itertools::Itertools;
+use std::mem;
 
 const LIGHT_ON: char = '#';
-const LIGHT_OFF: char = '.';
+const WORD_BITS: usize = u64::BITS as usize;
 
 #[aoc_generator(day18)]
 pub fn generate(inp: &str) -> Vec<Vec<char>> {
     inp.lines().map(|it| it.chars().collect_vec()).collect()
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn count_neighbours(grid: &[Vec<char>], row: usize, col: usize, grid_size: usize) -> usize {
-    let mut count = 0;
+// rows are packed into words, column `c` lives in bit `c % 64` of the row's word `c / 64`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    cells: Vec<u64>,
+}
+
+impl BitGrid {
+    fn from_rows(grid: &[Vec<char>]) -> Self {
+        let height = grid.len();
+        let width = grid.iter().map(Vec::len).max().unwrap_or_default();
+        let words_per_row = width.div_ceil(WORD_BITS);
+
+        let mut result = Self {
+            width,
+            height,
+            words_per_row,
+            cells: vec![0; words_per_row * height],
+        };
 
-    for (dx, dy) in iproduct!(-1i64..=1, -1i64..=1) {
-        if dx == 0 && dy == 0 {
-            continue;
+        for (row, grid_row) in grid.iter().enumerate() {
+            for (col, &cur) in grid_row.iter().enumerate() {
+                if cur == LIGHT_ON {
+                    result.turn_on(row, col);
+                }
+            }
         }
 
-        let x = usize::try_from(row as i64 + dx).ok();
-        let y = usize::try_from(col as i64 + dy).ok();
+        result
+    }
 
-        match (x, y) {
-            (Some(x), Some(y)) if x < grid_size && y < grid_size => {
-                let neighbour = grid[x][y];
-                count += usize::from(neighbour == LIGHT_ON);
-            }
-            _ => {}
-        };
+    fn turn_on(&mut self, row: usize, col: usize) {
+        self.cells[row * self.words_per_row + col / WORD_BITS] |= 1 << (col % WORD_BITS);
     }
 
-    count
-}
+    fn row(&self, row: usize) -> &[u64] {
+        &self.cells[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
 
-fn single_step(grid: &[Vec<char>]) -> Vec<Vec<char>> {
-    let mut result = Vec::new();
+    // keeps the unused bits past the last column switched off
+    const fn mask(&self, idx: usize) -> u64 {
+        let used = self.width % WORD_BITS;
+        if idx + 1 == self.words_per_row && used != 0 {
+            (1 << used) - 1
+        } else {
+            u64::MAX
+        }
+    }
 
-    let grid_size = grid.len();
+    fn turn_on_corner_lights(&mut self) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
 
-    for (row, grid_row) in grid.iter().enumerate() {
-        let mut new_row = Vec::new();
+        for (row, col) in [
+            (0, 0),
+            (0, self.width - 1),
+            (self.height - 1, 0),
+            (self.height - 1, self.width - 1),
+        ] {
+            self.turn_on(row, col);
+        }
+    }
 
-        for (col, &cur) in grid_row.iter().enumerate() {
-            let num_neighbours = count_neighbours(grid, row, col, grid_size);
+    fn step(&self, next: &mut Vec<u64>) {
+        next.clear();
+        next.resize(self.cells.len(), 0);
 
-            match cur {
-                LIGHT_ON if ![2, 3].contains(&num_neighbours) => new_row.push(LIGHT_OFF),
-                LIGHT_OFF if num_neighbours == 3 => new_row.push(LIGHT_ON),
-                _ => new_row.push(cur),
+        for row in 0..self.height {
+            let above = row.checked_sub(1).map_or(&[][..], |it| self.row(it));
+            let below = if row + 1 < self.height {
+                self.row(row + 1)
+            } else {
+                &[]
             };
+            let current = self.row(row);
+
+            for idx in 0..self.words_per_row {
+                let [west, alive, east] = shifted(current, idx);
+                let neighbours = shifted(above, idx)
+                    .into_iter()
+                    .chain(shifted(below, idx))
+                    .chain([west, east]);
+
+                // bit-sliced counter: ones, twos, and a sticky "four or more"
+                let (mut ones, mut twos, mut many) = (0u64, 0u64, 0u64);
+                for neighbour in neighbours {
+                    let carry = ones & neighbour;
+                    ones ^= neighbour;
+                    many |= twos & carry;
+                    twos ^= carry;
+                }
+
+                next[row * self.words_per_row + idx] =
+                    !many & twos & (ones | alive) & self.mask(idx);
+            }
         }
-
-        assert_eq!(new_row.len(), grid_size);
-
-        result.push(new_row);
     }
 
-    result
+    fn count_on(&self) -> usize {
+        self.cells.iter().map(|it| it.count_ones() as usize).sum()
+    }
 }
 
-fn turn_on_corner_lights(grid: &mut [Vec<char>], grid_size: usize) {
-    grid[0][0] = LIGHT_ON;
-    grid[0][grid_size] = LIGHT_ON;
-    grid[grid_size][0] = LIGHT_ON;
-    grid[grid_size][grid_size] = LIGHT_ON;
+// the west, centre and east neighbours of every cell in the word at `idx`
+fn shifted(row: &[u64], idx: usize) -> [u64; 3] {
+    let word = row.get(idx).copied().unwrap_or_default();
+    let prev = idx
+        .checked_sub(1)
+        .and_then(|it| row.get(it))
+        .copied()
+        .unwrap_or_default();
+    let next = row.get(idx + 1).copied().unwrap_or_default();
+
+    [
+        (word << 1) | (prev >> (WORD_BITS - 1)),
+        word,
+        (word >> 1) | (next << (WORD_BITS - 1)),
+    ]
 }
 
-fn run_steps(grid: &[Vec<char>], steps: usize, corners_always_on: bool) -> Vec<Vec<char>> {
-    let mut grid = grid.to_vec();
-
-    let grid_size = grid.len() - 1;
+fn run_steps(grid: &[Vec<char>], steps: usize, corners_always_on: bool) -> BitGrid {
+    let mut grid = BitGrid::from_rows(grid);
+    let mut next = Vec::with_capacity(grid.cells.len());
 
     for _ in 0..steps {
         if corners_always_on {
-            turn_on_corner_lights(&mut grid, grid_size);
+            grid.turn_on_corner_lights();
         }
 
-        grid = single_step(&grid);
+        grid.step(&mut next);
+        mem::swap(&mut grid.cells, &mut next);
     }
 
     if corners_always_on {
-        turn_on_corner_lights(&mut grid, grid_size);
+        grid.turn_on_corner_lights();
     }
 
     grid
 }
 
-fn count_active_lights(grid: &[Vec<char>]) -> usize {
-    grid.iter().flatten().filter(|&&it| it == LIGHT_ON).count()
-}
-
 #[aoc(day18, part1)]
 pub fn part1(grid: &[Vec<char>]) -> usize {
-    let transformed = run_steps(grid, 100, false);
-    count_active_lights(&transformed)
+    run_steps(grid, 100, false).count_on()
 }
 
 #[aoc(day18, part2)]
 pub fn part2(grid: &[Vec<char>]) -> usize {
-    let transformed = run_steps(grid, 100, true);
-    count_active_lights(&transformed)
+    run_steps(grid, 100, true).count_on()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::iproduct;
 
     #[test]
     fn test_sample_input_p1() {
@@ -120,13 +182,13 @@ mod tests {
                                ..##..\n\
                                ......\n\
                                ......";
-        let expected = generate(expected);
+        let expected = BitGrid::from_rows(&generate(expected));
 
         let gen = generate(inp);
         let res = run_steps(&gen, 4, false);
         assert_eq!(res, expected);
 
-        assert_eq!(count_active_lights(&res), 4);
+        assert_eq!(res.count_on(), 4);
     }
 
     #[test]
@@ -143,12 +205,78 @@ mod tests {
                                .##...\n\
                                #.#...\n\
                                ##...#";
-        let expected = generate(expected);
+        let expected = BitGrid::from_rows(&generate(expected));
 
         let gen = generate(inp);
         let res = run_steps(&gen, 5, true);
         assert_eq!(res, expected);
 
-        assert_eq!(count_active_lights(&res), 17);
+        assert_eq!(res.count_on(), 17);
+    }
+
+    // straightforward cell-by-cell version to check the packed engine against
+    fn reference_step(grid: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        let (height, width) = (grid.len(), grid[0].len());
+
+        (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|col| {
+                        let count = iproduct!(
+                            row.saturating_sub(1)..=row + 1,
+                            col.saturating_sub(1)..=col + 1
+                        )
+                        .filter(|&(r, c)| r < height && c < width)
+                        .filter(|&it| it != (row, col))
+                        .filter(|&(r, c)| grid[r][c])
+                        .count();
+
+                        count == 3 || (grid[row][col] && count == 2)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn to_chars(grid: &[Vec<bool>]) -> Vec<Vec<char>> {
+        grid.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&it| if it { LIGHT_ON } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        // widths on both sides of a word boundary
+        for (height, width) in [(7, 64), (20, 65), (33, 150)] {
+            let mut seed = 0x2545_f491_4f6c_dd1du64;
+            let mut grid = (0..height)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| {
+                            seed ^= seed << 13;
+                            seed ^= seed >> 7;
+                            seed ^= seed << 17;
+                            seed.is_multiple_of(3)
+                        })
+                        .collect_vec()
+                })
+                .collect_vec();
+
+            let start = to_chars(&grid);
+
+            for steps in 1..=12 {
+                grid = reference_step(&grid);
+
+                assert_eq!(
+                    run_steps(&start, steps, false),
+                    BitGrid::from_rows(&to_chars(&grid)),
+                    "{height}x{width} after {steps} steps"
+                );
+            }
+        }
     }
 }
@@ -1,39 +1,245 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use std::error::Error;
+use std::fmt::{self, Formatter};
+
+const GROUP_NAMES: [&str; 4] = ["passenger", "left", "right", "trunk"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PartitionError {
+    NotDivisible { total: usize, num_groups: usize },
+    NoPartition,
+    Overflow,
+}
+
+impl fmt::Display for PartitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDivisible { total, num_groups } => {
+                write!(
+                    f,
+                    "total weight {total} can't be split into {num_groups} equal groups"
+                )
+            }
+            Self::NoPartition => write!(f, "packages can't be split into equal groups"),
+            Self::Overflow => write!(f, "quantum entanglement doesn't fit in 128 bits"),
+        }
+    }
+}
+
+impl Error for PartitionError {}
 
 #[aoc_generator(day24)]
 pub fn generate(inp: &str) -> Vec<usize> {
     inp.lines().filter_map(|it| it.parse().ok()).collect()
 }
 
-fn find_sum(weights: &[usize], num_groups: usize) -> Option<usize> {
-    let total_weight = weights.iter().sum::<usize>();
-    let weight_per_group = total_weight / num_groups;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loadout {
+    // the passenger compartment comes first
+    groups: Vec<Vec<usize>>,
+    quantum_entanglement: u128,
+}
+
+impl Loadout {
+    fn group_weights(&self) -> Vec<usize> {
+        self.groups.iter().map(|it| it.iter().sum()).collect()
+    }
+}
+
+impl fmt::Display for Loadout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "quantum entanglement {}", self.quantum_entanglement)?;
 
-    for len in 1..weights.len() {
-        let min_weight = weights
-            .iter()
-            .combinations(len)
-            .filter(|it| it.iter().copied().sum::<usize>() == weight_per_group)
-            .map(|it| it.iter().copied().product::<usize>())
-            .min();
+        for (idx, (group, weight)) in self.groups.iter().zip(self.group_weights()).enumerate() {
+            let name = GROUP_NAMES
+                .get(idx)
+                .map_or_else(|| format!("group {}", idx + 1), ToString::to_string);
 
-        if min_weight.is_some() {
-            return min_weight;
+            writeln!(f, "{name} (weight {weight}): {}", group.iter().join(" "))?;
         }
+
+        Ok(())
+    }
+}
+
+fn fill_groups(weights: &[usize], groups: &mut [(usize, Vec<usize>)], target: usize) -> bool {
+    let Some((&weight, rest)) = weights.split_first() else {
+        return groups.iter().all(|(sum, _)| *sum == target);
+    };
+
+    for idx in 0..groups.len() {
+        // groups with the same fill level are interchangeable
+        let sum = groups[idx].0;
+        if sum + weight > target || groups[..idx].iter().any(|it| it.0 == sum) {
+            continue;
+        }
+
+        groups[idx].0 += weight;
+        groups[idx].1.push(weight);
+
+        if fill_groups(rest, groups, target) {
+            return true;
+        }
+
+        groups[idx].0 -= weight;
+        groups[idx].1.pop();
     }
 
-    None
+    false
+}
+
+fn partition(weights: &[usize], num_groups: usize, target: usize) -> Option<Vec<Vec<usize>>> {
+    let weights = weights
+        .iter()
+        .copied()
+        .sorted_unstable_by(|a, b| b.cmp(a))
+        .collect_vec();
+
+    let mut groups = vec![(0, Vec::new()); num_groups];
+    fill_groups(&weights, &mut groups, target).then(|| groups.into_iter().map(|it| it.1).collect())
+}
+
+struct FirstGroupSearch<'a> {
+    // sorted in descending order
+    weights: &'a [usize],
+    num_groups: usize,
+    target: usize,
+    chosen: Vec<usize>,
+    best: Option<Loadout>,
+    // a valid first group was found, but its product overflowed
+    overflowed: bool,
+}
+
+impl FirstGroupSearch<'_> {
+    fn remaining(&self) -> Vec<usize> {
+        let mut chosen = self.chosen.iter().peekable();
+        let mut result = Vec::with_capacity(self.weights.len() - self.chosen.len());
+
+        for (idx, weight) in self.weights.iter().enumerate() {
+            if chosen.next_if_eq(&&idx).is_none() {
+                result.push(*weight);
+            }
+        }
+
+        result
+    }
+
+    // an overflowed product (None) is worse than any that fits
+    fn is_hopeless(&self, product: Option<u128>) -> bool {
+        // a zero weight could still lower the product later on
+        self.best.as_ref().is_some_and(|it| {
+            product.is_none_or(|product| product >= it.quantum_entanglement)
+                && self.weights.last().is_some_and(|w| *w > 0)
+        })
+    }
+
+    fn search(&mut self, start: usize, slots: usize, needed: usize, product: Option<u128>) {
+        if slots == 0 {
+            if needed == 0 {
+                self.try_complete(product);
+            }
+            return;
+        }
+
+        if self.is_hopeless(product) {
+            return;
+        }
+
+        let n = self.weights.len();
+        // the smallest weights left are always at the end
+        if self.weights[n - slots..].iter().sum::<usize>() > needed {
+            return;
+        }
+
+        for idx in start..=n - slots {
+            let weight = self.weights[idx];
+            if weight > needed {
+                continue;
+            }
+
+            // the largest weights left start at `idx`, later picks only get lighter
+            if self.weights[idx..idx + slots].iter().sum::<usize>() < needed {
+                break;
+            }
+
+            let product = if weight == 0 {
+                Some(0)
+            } else {
+                product.and_then(|it| it.checked_mul(weight as u128))
+            };
+
+            self.chosen.push(idx);
+            self.search(idx + 1, slots - 1, needed - weight, product);
+            self.chosen.pop();
+        }
+    }
+
+    fn try_complete(&mut self, product: Option<u128>) {
+        if self.is_hopeless(product) || (product.is_none() && self.overflowed) {
+            return;
+        }
+
+        let Some(rest) = partition(&self.remaining(), self.num_groups - 1, self.target) else {
+            return;
+        };
+
+        let Some(product) = product else {
+            self.overflowed = true;
+            return;
+        };
+
+        let first_group = self.chosen.iter().map(|idx| self.weights[*idx]).collect();
+        self.best = Some(Loadout {
+            groups: std::iter::once(first_group).chain(rest).collect(),
+            quantum_entanglement: product,
+        });
+    }
+}
+
+fn find_loadout(weights: &[usize], num_groups: usize) -> Result<Loadout, PartitionError> {
+    let total = weights.iter().sum::<usize>();
+    if num_groups == 0 || total % num_groups != 0 {
+        return Err(PartitionError::NotDivisible { total, num_groups });
+    }
+
+    let sorted = weights
+        .iter()
+        .copied()
+        .sorted_unstable_by(|a, b| b.cmp(a))
+        .collect_vec();
+
+    let mut search = FirstGroupSearch {
+        weights: &sorted,
+        num_groups,
+        target: total / num_groups,
+        chosen: Vec::new(),
+        best: None,
+        overflowed: false,
+    };
+
+    for len in 1..=sorted.len() {
+        search.search(0, len, search.target, Some(1));
+
+        if let Some(loadout) = search.best {
+            return Ok(loadout);
+        }
+        if search.overflowed {
+            return Err(PartitionError::Overflow);
+        }
+    }
+
+    Err(PartitionError::NoPartition)
 }
 
 #[aoc(day24, part1)]
-pub fn part1(weights: &[usize]) -> Option<usize> {
-    find_sum(weights, 3)
+pub fn part1(weights: &[usize]) -> Result<Loadout, PartitionError> {
+    find_loadout(weights, 3)
 }
 
 #[aoc(day24, part2)]
-pub fn part2(weights: &[usize]) -> Option<usize> {
-    find_sum(weights, 4)
+pub fn part2(weights: &[usize]) -> Result<Loadout, PartitionError> {
+    find_loadout(weights, 4)
 }
 
 #[cfg(test)]
@@ -45,6 +251,98 @@ mod tests {
         let gen = vec![1usize, 2, 3, 4, 5, 7, 8, 9, 10, 11];
 
         let res = part1(&gen);
-        assert_eq!(res, Some(99));
+        assert_eq!(
+            res,
+            Ok(Loadout {
+                groups: vec![vec![11, 9], vec![10, 8, 2], vec![7, 5, 4, 3, 1]],
+                quantum_entanglement: 99,
+            })
+        );
+    }
+
+    #[test]
+    fn test_sample_p2() {
+        let gen = vec![1usize, 2, 3, 4, 5, 7, 8, 9, 10, 11];
+
+        let res = part2(&gen);
+        assert_eq!(res.as_ref().map(|it| it.quantum_entanglement), Ok(44));
+        assert_eq!(res.map(|it| it.group_weights()), Ok(vec![15; 4]));
+    }
+
+    #[test]
+    fn test_skips_incomplete_first_group() {
+        // {1, 11, 17} has the smallest product but leaves no two groups of 29
+        let gen = vec![1usize, 3, 8, 9, 10, 11, 13, 15, 17];
+
+        let res = part1(&gen);
+        assert_eq!(res.map(|it| it.quantum_entanglement), Ok(195));
+    }
+
+    #[test]
+    fn test_invalid_totals() {
+        let res = part1(&[1, 2, 3, 5]);
+        assert_eq!(
+            res,
+            Err(PartitionError::NotDivisible {
+                total: 11,
+                num_groups: 3
+            })
+        );
+        assert_eq!(
+            res.map_err(|it| it.to_string()),
+            Err(String::from(
+                "total weight 11 can't be split into 3 equal groups"
+            ))
+        );
+
+        assert_eq!(part1(&[3, 3, 6, 9]), Err(PartitionError::NoPartition));
+    }
+
+    #[test]
+    fn test_display_loadout() {
+        let gen = vec![1usize, 2, 3, 4, 5, 7, 8, 9, 10, 11];
+
+        let res = part2(&gen).map(|it| it.to_string());
+        assert_eq!(
+            res,
+            Ok(String::from(
+                "quantum entanglement 44\n\
+                 passenger (weight 15): 11 4\n\
+                 left (weight 15): 10 5\n\
+                 right (weight 15): 9 3 2 1\n\
+                 trunk (weight 15): 8 7\n"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_large_quantum_entanglement() {
+        let gen = vec![1usize << 40; 6];
+
+        let res = part1(&gen).map(|it| it.quantum_entanglement);
+        assert_eq!(res, Ok(1u128 << 80));
+    }
+
+    #[test]
+    fn test_quantum_entanglement_overflow() {
+        // every group holds four packages, 2^160 in all
+        let gen = vec![1usize << 40; 12];
+
+        let res = part1(&gen);
+        assert_eq!(res, Err(PartitionError::Overflow));
+    }
+
+    #[test]
+    fn test_many_packages() {
+        let gen = (1..=60).collect_vec();
+
+        let res = part1(&gen).expect("1..=60 splits into three groups of 610");
+        assert_eq!(res.group_weights(), vec![610; 3]);
+        // the eleven heaviest packages only weigh 605
+        assert_eq!(res.groups[0].len(), 12);
+        assert_eq!(
+            res.quantum_entanglement,
+            res.groups[0].iter().map(|it| *it as u128).product::<u128>()
+        );
     }
 }
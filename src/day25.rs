@@ -1,5 +1,7 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
 use parse_display::{Display, FromStr};
+use std::collections::HashMap;
 
 #[derive(Display, FromStr, Copy, Clone, Debug)]
 #[display("To continue, please consult the code grid in the manual.  Enter the code at row {row}, column {col}.")]
@@ -21,10 +23,180 @@ const fn get_index_in_cantor(row: usize, col: usize) -> usize {
     sum_first_n_digits(col) + sum_first_n_digits(col + row - 2) - sum_first_n_digits(col - 1)
 }
 
+const fn get_position_in_cantor(index: usize) -> (usize, usize) {
+    // the diagonal holding `index` is the first one whose last index reaches it
+    let mut diagonal = (2 * index).isqrt();
+    while sum_first_n_digits(diagonal) < index {
+        diagonal += 1;
+    }
+    while diagonal > 1 && sum_first_n_digits(diagonal - 1) >= index {
+        diagonal -= 1;
+    }
+
+    let col = index - sum_first_n_digits(diagonal - 1);
+    (diagonal + 1 - col, col)
+}
+
+const fn pow_mod(mut base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    base %= modulus;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exp >>= 1;
+    }
+
+    result
+}
+
+const fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+fn inverse_mod(value: u64, modulus: u64) -> Option<u64> {
+    let (mut old_r, mut r) = (i128::from(value % modulus), i128::from(modulus));
+    let (mut old_s, mut s) = (1i128, 0i128);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+
+    (old_r == 1).then(|| old_s.rem_euclid(i128::from(modulus)) as u64)
+}
+
+fn digits(value: u64) -> usize {
+    value.to_string().len()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CodeGenerator {
+    pub first: u64,
+    pub multiplier: u64,
+    pub modulus: u64,
+}
+
+impl CodeGenerator {
+    pub const MANUAL: Self = Self {
+        first: 20_151_125,
+        multiplier: 252_533,
+        modulus: 33_554_393,
+    };
+
+    // codes are numbered from 1 in the order they are filled in
+    pub const fn code(&self, index: usize) -> u64 {
+        let factor = pow_mod(self.multiplier, index as u64 - 1, self.modulus);
+        mul_mod(self.first, factor, self.modulus)
+    }
+
+    // baby-step giant-step search for the smallest `n` with `code(n) == code`
+    pub fn find_index(&self, code: u64) -> Option<usize> {
+        let modulus = self.modulus;
+        let (Some(first_inverse), Some(giant_step)) = (
+            inverse_mod(self.first, modulus),
+            inverse_mod(self.multiplier, modulus),
+        ) else {
+            return self.scan_index(code);
+        };
+        let target = mul_mod(code, first_inverse, modulus);
+
+        let steps = (modulus - 1).isqrt() + 1;
+
+        let mut baby_steps = HashMap::new();
+        let mut power = 1 % modulus;
+        for j in 0..steps {
+            baby_steps.entry(power).or_insert(j);
+            power = mul_mod(power, self.multiplier, modulus);
+        }
+
+        let giant_step = pow_mod(giant_step, steps, modulus);
+        let mut gamma = target;
+        for i in 0..steps {
+            if let Some(j) = baby_steps.get(&gamma) {
+                return usize::try_from(i * steps + j + 1).ok();
+            }
+            gamma = mul_mod(gamma, giant_step, modulus);
+        }
+
+        None
+    }
+
+    // without inverses the codes can't be walked backwards, but there are only
+    // `modulus` different ones, so every code shows up within that many steps
+    fn scan_index(&self, code: u64) -> Option<usize> {
+        let mut current = self.first % self.modulus;
+        for index in 1..=self.modulus {
+            if current == code {
+                return usize::try_from(index).ok();
+            }
+            current = mul_mod(current, self.multiplier, self.modulus);
+        }
+
+        None
+    }
+
+    pub fn render(&self, first: &Entry, rows: usize, cols: usize) -> String {
+        let row_range = first.row..first.row + rows;
+        let col_range = first.col..first.col + cols;
+
+        let label_width = digits(row_range.end as u64 - 1);
+        let width = digits(self.modulus.saturating_sub(1)).max(digits(col_range.end as u64 - 1));
+
+        let mut lines = Vec::with_capacity(rows + 2);
+        let header = col_range
+            .clone()
+            .map(|col| format!(" {col:^width$} "))
+            .join("");
+        lines.push(format!("{:label_width$}  |{header}", ""));
+
+        let separator = format!("{}+", "-".repeat(width + 1)).repeat(cols);
+        lines.push(format!("{}+{separator}", "-".repeat(label_width + 2)));
+
+        for row in row_range {
+            let cells = col_range
+                .clone()
+                .map(|col| format!(" {:>width$} ", self.code(get_index_in_cantor(row, col))))
+                .join("");
+            lines.push(format!(" {row:>label_width$} |{cells}"));
+        }
+
+        format!("{}\n", lines.iter().map(|it| it.trim_end()).join("\n"))
+    }
+
+    pub fn find_entry(&self, code: u64) -> Option<Entry> {
+        let (row, col) = get_position_in_cantor(self.find_index(code)?);
+        Some(Entry { row, col })
+    }
+}
+
 #[aoc(day25, part1)]
-pub fn part1(inp: &Entry) -> usize {
-    let num_repetitions = get_index_in_cantor(inp.row, inp.col);
-    (1..num_repetitions).fold(20_151_125, |acc, _| (acc * 252_533) % 33_554_393)
+pub const fn part1(inp: &Entry) -> u64 {
+    CodeGenerator::MANUAL.code(get_index_in_cantor(inp.row, inp.col))
+}
+
+// look up where the answer code first shows up in the grid
+#[aoc(day25, part1, Inverse)]
+pub fn part1_inverse(inp: &Entry) -> Option<Entry> {
+    CodeGenerator::MANUAL.find_entry(part1(inp))
+}
+
+const TABLE_RADIUS: usize = 2;
+
+// show the codes surrounding the answer the way the manual prints them
+#[aoc(day25, part1, Table)]
+pub fn part1_table(inp: &Entry) -> String {
+    let first = Entry {
+        row: inp.row.saturating_sub(TABLE_RADIUS).max(1),
+        col: inp.col.saturating_sub(TABLE_RADIUS).max(1),
+    };
+    let rows = inp.row + TABLE_RADIUS + 1 - first.row;
+    let cols = inp.col + TABLE_RADIUS + 1 - first.col;
+
+    format!("\n{}", CodeGenerator::MANUAL.render(&first, rows, cols))
 }
 
 #[cfg(test)]
@@ -51,4 +223,140 @@ mod tests {
             assert_eq!(res, expected + 1);
         }
     }
+
+    fn iterative(generator: CodeGenerator, index: usize) -> u64 {
+        (1..index).fold(generator.first, |acc, _| {
+            (acc * generator.multiplier) % generator.modulus
+        })
+    }
+
+    #[test]
+    fn test_sample_codes() {
+        let generator = CodeGenerator::MANUAL;
+        assert_eq!(generator.code(get_index_in_cantor(1, 1)), 20_151_125);
+        assert_eq!(generator.code(get_index_in_cantor(2, 1)), 31_916_031);
+        assert_eq!(generator.code(get_index_in_cantor(6, 6)), 27_995_004);
+        assert_eq!(part1(&Entry { row: 4, col: 3 }), 21_345_942);
+    }
+
+    #[test]
+    fn test_matches_iterative() {
+        let generators = [
+            CodeGenerator::MANUAL,
+            CodeGenerator {
+                first: 3,
+                multiplier: 7,
+                modulus: 101,
+            },
+            CodeGenerator {
+                first: 5,
+                multiplier: 12,
+                modulus: 18,
+            },
+        ];
+
+        for generator in generators {
+            for row in 1..=40 {
+                for col in 1..=40 {
+                    let index = get_index_in_cantor(row, col);
+                    assert_eq!(
+                        generator.code(index),
+                        iterative(generator, index),
+                        "{generator:?} at row {row}, column {col}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cantor_positions() {
+        for row in 1..=30 {
+            for col in 1..=30 {
+                let index = get_index_in_cantor(row, col);
+                assert_eq!(get_position_in_cantor(index), (row, col));
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_index() {
+        let generator = CodeGenerator::MANUAL;
+        for index in [1, 2, 21, 12_345, 1_554_364] {
+            assert_eq!(generator.find_index(generator.code(index)), Some(index));
+        }
+
+        // the codes repeat every 16_777_196 steps
+        let code = generator.code(18_331_560);
+        assert_eq!(generator.find_index(code), Some(1_554_364));
+
+        let entry = generator.find_entry(27_995_004).map(|it| (it.row, it.col));
+        assert_eq!(entry, Some((6, 6)));
+    }
+
+    #[test]
+    fn test_find_index_first_occurrence() {
+        // 2 has order 10 modulo 11, so every code repeats after ten steps
+        let generator = CodeGenerator {
+            first: 3,
+            multiplier: 2,
+            modulus: 11,
+        };
+
+        for index in 1..=10 {
+            assert_eq!(
+                generator.find_index(generator.code(index + 10)),
+                Some(index)
+            );
+        }
+        assert_eq!(generator.find_index(0), None);
+    }
+
+    #[test]
+    fn test_find_index_without_inverse() {
+        // neither 5 nor 12 can be inverted modulo 18: the codes run 5, 6, 0, 0, ...
+        let generator = CodeGenerator {
+            first: 5,
+            multiplier: 12,
+            modulus: 18,
+        };
+
+        assert_eq!(generator.find_index(5), Some(1));
+        assert_eq!(generator.find_index(6), Some(2));
+        assert_eq!(generator.find_index(generator.code(7)), Some(3));
+        assert_eq!(generator.find_index(1), None);
+    }
+
+    #[test]
+    fn test_render_manual_corner() {
+        let expected = "   |    1         2         3         4         5         6\n\
+                        ---+---------+---------+---------+---------+---------+---------+\n \
+                         1 | 20151125  18749137  17289845  30943339  10071777  33511524\n \
+                         2 | 31916031  21629792  16929656   7726640  15514188   4041754\n \
+                         3 | 16080970   8057251   1601130   7981243  11661866  16474243\n \
+                         4 | 24592653  32451966  21345942   9380097  10600672  31527494\n \
+                         5 |    77061  17552253  28094349   6899651   9250759  31663883\n \
+                         6 | 33071741   6796745  25397450  24659492   1534922  27995004\n";
+
+        let res = CodeGenerator::MANUAL.render(&Entry { row: 1, col: 1 }, 6, 6);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_render_window() {
+        let generator = CodeGenerator {
+            first: 1,
+            multiplier: 1,
+            modulus: 2,
+        };
+
+        let res = generator.render(&Entry { row: 9, col: 11 }, 2, 2);
+        assert_eq!(
+            res,
+            "    | 11  12\n\
+             ----+---+---+\n  \
+             9 |  1   1\n \
+             10 |  1   1\n"
+        );
+    }
 }
@@ -0,0 +1,276 @@
+use crate::held_karp::{self, Goal, Solution};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// ties can grow factorially, so only this many are spelled out
+const MAX_ROUTES: usize = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Symmetric,
+    Asymmetric,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestRoutes {
+    pub length: i64,
+    pub routes: Vec<Vec<String>>,
+    pub ties: u64,
+}
+
+impl Display for BestRoutes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .routes
+            .iter()
+            .map(|it| format!("{} = {}", it.join(" -> "), self.length))
+            .join("\n");
+        write!(f, "{lines}")?;
+
+        let listed = u64::try_from(self.routes.len()).unwrap_or(u64::MAX);
+        if self.ties > listed {
+            write!(f, "\n... and {} more ties", self.ties - listed)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingEdges(Vec<(String, String)>);
+
+impl Display for MissingEdges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pairs = self
+            .0
+            .iter()
+            .map(|(from, to)| format!("{from} -> {to}"))
+            .join(", ");
+
+        write!(f, "no connection between: {pairs}")
+    }
+}
+
+impl Error for MissingEdges {}
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    kind: Kind,
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    weights: Vec<Vec<Option<i64>>>,
+}
+
+impl Graph {
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            names: Vec::new(),
+            ids: HashMap::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, name: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = NodeId(self.names.len());
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+
+        for row in &mut self.weights {
+            row.push(None);
+        }
+        self.weights.push(vec![None; self.names.len()]);
+
+        id
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str, weight: i64) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+
+        self.weights[from.0][to.0] = Some(weight);
+        if self.kind == Kind::Symmetric {
+            self.weights[to.0][from.0] = Some(weight);
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.names.len()).map(NodeId)
+    }
+
+    pub fn weight(&self, from: NodeId, to: NodeId) -> Option<i64> {
+        self.weights[from.0][to.0]
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id.0]
+    }
+
+    pub fn route_length(&self, route: &[NodeId]) -> Option<i64> {
+        route
+            .windows(2)
+            .map(|pair| self.weight(pair[0], pair[1]))
+            .sum()
+    }
+
+    pub fn missing_edges(&self) -> Vec<(NodeId, NodeId)> {
+        let mut result = Vec::new();
+
+        for from in self.nodes() {
+            for to in self.nodes().filter(|it| *it != from) {
+                if self.kind == Kind::Symmetric && to < from {
+                    continue;
+                }
+
+                if self.weight(from, to).is_none() {
+                    result.push((from, to));
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn ensure_complete(&self) -> Result<(), MissingEdges> {
+        let pairs = self
+            .missing_edges()
+            .into_iter()
+            .map(|(from, to)| (self.name(from).to_string(), self.name(to).to_string()))
+            .collect_vec();
+
+        if pairs.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingEdges(pairs))
+        }
+    }
+
+    fn cost_matrix(&self, cost: impl Fn(NodeId, NodeId) -> Option<i64>) -> Vec<Vec<Option<i64>>> {
+        self.nodes()
+            .map(|a| self.nodes().map(|b| cost(a, b)).collect())
+            .collect()
+    }
+
+    fn to_best_routes(&self, solution: Solution, closed: bool) -> BestRoutes {
+        let routes = solution
+            .orders
+            .into_iter()
+            .map(|mut order| {
+                if closed {
+                    order.extend(order.first().copied());
+                }
+
+                order
+                    .into_iter()
+                    .map(|it| self.name(NodeId(it)).to_string())
+                    .collect()
+            })
+            .collect();
+
+        BestRoutes {
+            length: solution.value,
+            routes,
+            ties: solution.ties,
+        }
+    }
+
+    pub fn best_path(
+        &self,
+        goal: Goal,
+        cost: impl Fn(NodeId, NodeId) -> Option<i64>,
+    ) -> Option<BestRoutes> {
+        held_karp::best_path(&self.cost_matrix(cost), goal, MAX_ROUTES)
+            .map(|it| self.to_best_routes(it, false))
+    }
+
+    pub fn best_cycle(
+        &self,
+        goal: Goal,
+        cost: impl Fn(NodeId, NodeId) -> Option<i64>,
+    ) -> Option<BestRoutes> {
+        held_karp::best_cycle(&self.cost_matrix(cost), goal, MAX_ROUTES)
+            .map(|it| self.to_best_routes(it, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetric_edges() {
+        let mut graph = Graph::new(Kind::Symmetric);
+        let london = graph.add_node("London");
+        graph.add_edge("London", "Dublin", 464);
+        let dublin = graph.add_node("Dublin");
+
+        assert_eq!(graph.nodes().count(), 2);
+        assert_eq!(graph.weight(london, dublin), Some(464));
+        assert_eq!(graph.weight(dublin, london), Some(464));
+        assert_eq!(graph.name(dublin), "Dublin");
+        assert!(graph.ensure_complete().is_ok());
+
+        graph.add_edge("London", "Belfast", 518);
+        let belfast = graph.add_node("Belfast");
+        assert_eq!(graph.missing_edges(), vec![(dublin, belfast)]);
+        assert_eq!(
+            graph.ensure_complete().map_err(|it| it.to_string()),
+            Err(String::from("no connection between: Dublin -> Belfast"))
+        );
+    }
+
+    #[test]
+    fn test_best_routes_display() {
+        let routes = BestRoutes {
+            length: 605,
+            routes: vec![vec![
+                "London".to_string(),
+                "Dublin".to_string(),
+                "Belfast".to_string(),
+            ]],
+            ties: 1,
+        };
+
+        assert_eq!(routes.to_string(), "London -> Dublin -> Belfast = 605");
+
+        let routes = BestRoutes { ties: 4, ..routes };
+        assert_eq!(
+            routes.to_string(),
+            "London -> Dublin -> Belfast = 605\n... and 3 more ties"
+        );
+    }
+
+    #[test]
+    fn test_asymmetric_edges() {
+        let mut graph = Graph::new(Kind::Asymmetric);
+        graph.add_edge("Alice", "Bob", 54);
+        let alice = graph.add_node("Alice");
+        let bob = graph.add_node("Bob");
+
+        assert_eq!(graph.weight(alice, bob), Some(54));
+        assert_eq!(graph.weight(bob, alice), None);
+    }
+
+    #[test]
+    fn test_route_length() {
+        let mut graph = Graph::new(Kind::Asymmetric);
+        graph.add_edge("Alice", "Bob", 54);
+        graph.add_edge("Bob", "Carol", -7);
+        let alice = graph.add_node("Alice");
+        let bob = graph.add_node("Bob");
+        let carol = graph.add_node("Carol");
+
+        assert_eq!(graph.route_length(&[alice, bob, carol]), Some(47));
+        assert_eq!(graph.route_length(&[carol, bob, alice]), None);
+        assert_eq!(graph.route_length(&[alice]), Some(0));
+    }
+}
@@ -0,0 +1,350 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Goal {
+    Shortest,
+    Longest,
+}
+
+impl Goal {
+    const fn worst(self) -> i64 {
+        match self {
+            Self::Shortest => i64::MAX,
+            Self::Longest => i64::MIN,
+        }
+    }
+
+    const fn is_better(self, candidate: i64, current: i64) -> bool {
+        match self {
+            Self::Shortest => candidate < current,
+            Self::Longest => candidate > current,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub value: i64,
+    // at most `max_orders` of the `ties` optimal orders are listed
+    pub orders: Vec<Vec<usize>>,
+    pub ties: u64,
+}
+
+struct Table<'a> {
+    dist: &'a [Vec<Option<i64>>],
+    goal: Goal,
+    // dp[mask * n + last] = best length of a path visiting exactly `mask` and ending in `last`
+    dp: Vec<i64>,
+    // ways[mask * n + last] = how many paths reach that best length
+    ways: Vec<u64>,
+}
+
+impl<'a> Table<'a> {
+    fn fill(dist: &'a [Vec<Option<i64>>], goal: Goal, starts: &[usize]) -> Self {
+        let n = dist.len();
+        let unreachable = goal.worst();
+        let mut dp = vec![unreachable; (1 << n) * n];
+        let mut ways = vec![0u64; (1 << n) * n];
+
+        for &start in starts {
+            dp[(1 << start) * n + start] = 0;
+            ways[(1 << start) * n + start] = 1;
+        }
+
+        for mask in 1..(1usize << n) {
+            for last in 0..n {
+                let cur = dp[mask * n + last];
+                let cur_ways = ways[mask * n + last];
+                if cur == unreachable {
+                    continue;
+                }
+
+                for next in (0..n).filter(|it| mask & (1 << it) == 0) {
+                    let Some(edge) = dist[last][next] else {
+                        continue;
+                    };
+
+                    let idx = (mask | (1 << next)) * n + next;
+                    let candidate = cur + edge;
+                    if goal.is_better(candidate, dp[idx]) {
+                        dp[idx] = candidate;
+                        ways[idx] = cur_ways;
+                    } else if candidate == dp[idx] {
+                        ways[idx] = ways[idx].saturating_add(cur_ways);
+                    }
+                }
+            }
+        }
+
+        Self {
+            dist,
+            goal,
+            dp,
+            ways,
+        }
+    }
+
+    const fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    const fn full_mask(&self) -> usize {
+        (1 << self.len()) - 1
+    }
+
+    fn get(&self, mask: usize, last: usize) -> Option<i64> {
+        let value = self.dp[mask * self.len() + last];
+        (value != self.goal.worst()).then_some(value)
+    }
+
+    fn ways(&self, mask: usize, last: usize) -> u64 {
+        self.ways[mask * self.len() + last]
+    }
+
+    // stops once `out` holds `max_orders` orders, and skips branches that
+    // `feasible(mask, end)` rules out for the order's final node `end`
+    fn collect_orders(
+        &self,
+        mask: usize,
+        last: usize,
+        suffix: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+        max_orders: usize,
+        feasible: &impl Fn(usize, usize) -> bool,
+    ) {
+        let end = suffix.first().copied().unwrap_or(last);
+        if out.len() >= max_orders || !feasible(mask, end) {
+            return;
+        }
+
+        suffix.push(last);
+
+        if mask.is_power_of_two() {
+            out.push(suffix.iter().rev().copied().collect());
+        } else if let Some(target) = self.get(mask, last) {
+            let prev_mask = mask & !(1 << last);
+
+            for prev in (0..self.len()).filter(|it| prev_mask & (1 << it) != 0) {
+                let via_prev = self
+                    .get(prev_mask, prev)
+                    .zip(self.dist[prev][last])
+                    .map(|(path, edge)| path + edge);
+
+                if via_prev == Some(target) {
+                    self.collect_orders(prev_mask, prev, suffix, out, max_orders, feasible);
+                }
+            }
+        }
+
+        suffix.pop();
+    }
+
+    fn is_symmetric(&self) -> bool {
+        (0..self.len()).all(|a| (0..a).all(|b| self.dist[a][b] == self.dist[b][a]))
+    }
+}
+
+fn best_of(goal: Goal, values: impl Iterator<Item = i64>) -> Option<i64> {
+    values.reduce(|acc, it| if goal.is_better(it, acc) { it } else { acc })
+}
+
+pub fn best_path(dist: &[Vec<Option<i64>>], goal: Goal, max_orders: usize) -> Option<Solution> {
+    let n = dist.len();
+    if n == 0 {
+        return None;
+    }
+
+    let starts = (0..n).collect::<Vec<_>>();
+    let table = Table::fill(dist, goal, &starts);
+    let full = table.full_mask();
+
+    let value = best_of(goal, (0..n).filter_map(|last| table.get(full, last)))?;
+
+    let lasts = (0..n)
+        .filter(|it| table.get(full, *it) == Some(value))
+        .collect::<Vec<_>>();
+    let mut ties = lasts.iter().fold(0u64, |acc, last| {
+        acc.saturating_add(table.ways(full, *last))
+    });
+
+    // a reversed path has the same length, so only list one direction
+    let symmetric = table.is_symmetric();
+    if symmetric && n > 1 {
+        ties /= 2;
+    }
+    // some node left in `mask` has to be the start, and it must not exceed `end`
+    let feasible = |mask: usize, end: usize| !symmetric || mask & ((2 << end) - 1) != 0;
+
+    let mut orders = Vec::new();
+    for last in lasts {
+        table.collect_orders(
+            full,
+            last,
+            &mut Vec::new(),
+            &mut orders,
+            max_orders,
+            &feasible,
+        );
+    }
+    orders.sort_unstable();
+
+    Some(Solution {
+        value,
+        orders,
+        ties,
+    })
+}
+
+pub fn best_cycle(dist: &[Vec<Option<i64>>], goal: Goal, max_orders: usize) -> Option<Solution> {
+    let n = dist.len();
+    if n == 0 {
+        return None;
+    }
+
+    // a cycle can be rotated to start anywhere, so fix the first node
+    let table = Table::fill(dist, goal, &[0]);
+    let full = table.full_mask();
+    let closed = |last: usize| {
+        table
+            .get(full, last)
+            .zip(dist[last][0])
+            .map(|(path, edge)| path + edge)
+    };
+
+    let value = best_of(goal, (0..n).filter_map(closed))?;
+
+    let lasts = (0..n)
+        .filter(|it| closed(*it) == Some(value))
+        .collect::<Vec<_>>();
+    let mut ties = lasts.iter().fold(0u64, |acc, last| {
+        acc.saturating_add(table.ways(full, *last))
+    });
+
+    let symmetric = table.is_symmetric();
+    if symmetric && n >= 3 {
+        ties /= 2;
+    }
+    // the node after 0 is still in `mask`, and it must come before `end`
+    let feasible = |mask: usize, end: usize| {
+        !symmetric || n < 3 || mask == 1 || (mask & !1) & ((1 << end) - 1) != 0
+    };
+
+    let mut orders = Vec::new();
+    for last in lasts {
+        table.collect_orders(
+            full,
+            last,
+            &mut Vec::new(),
+            &mut orders,
+            max_orders,
+            &feasible,
+        );
+    }
+    orders.sort_unstable();
+
+    Some(Solution {
+        value,
+        orders,
+        ties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_matrix(dist: &[[i64; 3]]) -> Vec<Vec<Option<i64>>> {
+        dist.iter()
+            .map(|row| row.iter().map(|it| (*it >= 0).then_some(*it)).collect())
+            .collect()
+    }
+
+    fn sample() -> Vec<Vec<Option<i64>>> {
+        to_matrix(&[[0, 464, 518], [464, 0, 141], [518, 141, 0]])
+    }
+
+    #[test]
+    fn test_best_path() {
+        let shortest = best_path(&sample(), Goal::Shortest, 10);
+        assert_eq!(
+            shortest,
+            Some(Solution {
+                value: 605,
+                orders: vec![vec![0, 1, 2]],
+                ties: 1,
+            })
+        );
+
+        let longest = best_path(&sample(), Goal::Longest, 10);
+        assert_eq!(
+            longest,
+            Some(Solution {
+                value: 982,
+                orders: vec![vec![1, 0, 2]],
+                ties: 1,
+            })
+        );
+
+        assert_eq!(best_path(&[], Goal::Shortest, 10), None);
+    }
+
+    #[test]
+    fn test_best_cycle() {
+        let res = best_cycle(&sample(), Goal::Shortest, 10);
+        assert_eq!(
+            res,
+            Some(Solution {
+                value: 1123,
+                orders: vec![vec![0, 1, 2]],
+                ties: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_ties() {
+        let dist = to_matrix(&[[0, 1, 1], [1, 0, 1], [1, 1, 0]]);
+        let res = best_path(&dist, Goal::Shortest, 10);
+        assert_eq!(res.as_ref().map(|it| it.ties), Some(3));
+        assert_eq!(
+            res.map(|it| it.orders),
+            Some(vec![vec![0, 1, 2], vec![0, 2, 1], vec![1, 0, 2]])
+        );
+    }
+
+    #[test]
+    fn test_missing_edges() {
+        let dist = to_matrix(&[[0, 464, 518], [464, 0, -1], [518, -1, 0]]);
+
+        let path = best_path(&dist, Goal::Longest, 10);
+        assert_eq!(
+            path,
+            Some(Solution {
+                value: 982,
+                orders: vec![vec![1, 0, 2]],
+                ties: 1,
+            })
+        );
+
+        assert_eq!(best_cycle(&dist, Goal::Shortest, 10), None);
+    }
+
+    #[test]
+    fn test_uniform_weights() {
+        let n = 14;
+        let dist = (0..n)
+            .map(|a| (0..n).map(|b| (a != b).then_some(1)).collect())
+            .collect::<Vec<_>>();
+
+        let path = best_path(&dist, Goal::Shortest, 5).expect("Graph is complete");
+        assert_eq!(path.value, 13);
+        assert_eq!(path.orders.len(), 5);
+        // 14! / 2
+        assert_eq!(path.ties, 43_589_145_600);
+
+        let cycle = best_cycle(&dist, Goal::Longest, 5).expect("Graph is complete");
+        assert_eq!(cycle.value, 14);
+        assert_eq!(cycle.orders.len(), 5);
+        assert!(cycle.orders.iter().all(|it| it[0] == 0 && it[1] < it[13]));
+        // 13! / 2
+        assert_eq!(cycle.ties, 3_113_510_400);
+    }
+}
@@ -32,5 +32,7 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
+mod graph;
+mod held_karp;
 
 aoc_runner_derive::aoc_lib! { year = 2015 }